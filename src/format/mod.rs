@@ -0,0 +1,693 @@
+use std::mem;
+
+use crate::interpreter::{
+	error::{LambisError, Span},
+	parser::{self, lexer::{self, Comment, Token}, pattern::{Case, Pattern}, ASTNode, Operation, Parameter, Value},
+};
+
+// where a node is written, which decides what it has to be parenthesized for
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+enum Place {
+	// statements and the inside of parentheses, the only places definitions are written in
+	Statement,
+	// bodies, definition values and the inside of `|_|`, `[_]` and `{_}`,
+	// where a call takes everything up to a period as its arguments
+	Open,
+	// switch actions, the only places a switch is written without parentheses besides the open ones
+	Action,
+	// guards and the right side of `+`, `-`, `++`, `:`, `&&` and `||`, which take a whole chain of operations
+	Chain,
+	// call arguments and array items, where operations are written without spaces
+	Argument,
+	// the right side of every other operation, a single value
+	Operand,
+}
+
+impl Place {
+	// names don't take arguments there
+	fn calls(self) -> bool {
+		!matches!(self, Place::Statement | Place::Open)
+	}
+}
+
+// whether a statement ending with the written code needs a period
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+enum Ending {
+	// it does, the code would take whatever comes next as arguments otherwise
+	Open,
+	// it can go without one
+	Closed,
+}
+
+// the lambdas the parser makes out of `[]`, `{}`, `!` and operators missing operands
+enum Sugar<'a> {
+	IntegerPart,
+	RationalPart,
+	Print,
+	// `(+)`, `(+ 1)` and `(1 +)`
+	Section(Option<&'a ASTNode>, &'a Operation, Option<&'a ASTNode>),
+}
+
+fn symbol(op: &Operation) -> &'static str {
+	match op {
+		Operation::Addition => "+",
+		Operation::Substraction => "-",
+		Operation::Multiplication => "*",
+		Operation::Division => "/",
+		Operation::FloorDivision => "//",
+		Operation::Remainder => "%",
+		Operation::Exponent => "^",
+		Operation::Concat => "++",
+		Operation::Index => "!!",
+		Operation::Cons => ":",
+		Operation::LessEqual => "<=",
+		Operation::Less => "<",
+		Operation::Greater => ">",
+		Operation::GreaterEqual => ">=",
+		Operation::Equal => "=",
+		Operation::NotEqual => "!=",
+		Operation::And => "&&",
+		Operation::Or => "||",
+	}
+}
+
+// the right side of these is parsed with every operation following it, so `a - b + c` is `a - (b + c)`
+fn chains(op: &Operation) -> bool {
+	matches!(op, Operation::Addition | Operation::Substraction | Operation::Concat | Operation::Cons | Operation::And | Operation::Or)
+}
+
+// and these continue with any operation after their single right value, so `a * b + c` is `(a * b) + c`
+fn repeats(op: &Operation) -> bool {
+	matches!(op,
+		Operation::Multiplication
+		| Operation::Division
+		| Operation::FloorDivision
+		| Operation::Remainder
+		| Operation::Exponent
+		| Operation::Index
+	)
+}
+
+fn is_variable(node: &ASTNode, name: &str) -> bool {
+	matches!(node, ASTNode::Value(Value::Variable(variable, _)) if variable == name)
+}
+
+fn sugar<'a>(params: &[Parameter], content: &'a ASTNode) -> Option<Sugar<'a>> {
+	let x: Parameter = Parameter::eager("X");
+	let y: Parameter = Parameter::eager("Y");
+
+	Some(match (params, content) {
+		([param], ASTNode::IntegerPart(value, _)) if *param == x && is_variable(value, "X") => Sugar::IntegerPart,
+		([param], ASTNode::RationalPart(value, _)) if *param == x && is_variable(value, "X") => Sugar::RationalPart,
+		([param], ASTNode::Print(value, _)) if *param == x && is_variable(value, "X") => Sugar::Print,
+		([first, second], ASTNode::Operation {left, operation, right, ..})
+			if *first == x && *second == y && is_variable(left, "X") && is_variable(right, "Y") => Sugar::Section(None, operation, None),
+		([param], ASTNode::Operation {left, operation, right, ..}) if *param == x && is_variable(left, "X") => Sugar::Section(None, operation, Some(right)),
+		([param], ASTNode::Operation {left, operation, right, ..}) if *param == y && is_variable(right, "Y") => Sugar::Section(Some(left), operation, None),
+		_ => return None,
+	})
+}
+
+fn decimal(number: f64) -> String {
+	let text: String = number.to_string();
+	if text.contains('.') {
+		text
+	} else {
+		format!("{text}.0")
+	}
+}
+
+fn literal(value: &Value) -> String {
+	match value {
+		Value::None => "()".to_owned(),
+		Value::String(s) => format!("{s:?}"),
+		Value::Decimal(number) => decimal(*number),
+		other => other.to_string(),
+	}
+}
+
+// whether `node` written as a statement would take the code after it as arguments
+fn takes_arguments(node: &ASTNode) -> bool {
+	match node {
+		ASTNode::Value(Value::Variable(..)) => true,
+		ASTNode::Value(Value::Lambda {args_def, content, ..}) => match sugar(args_def, content) {
+			Some(Sugar::Print) | None => takes_arguments(content),
+			Some(_) => false,
+		},
+
+		ASTNode::Definition {value: body, ..} | ASTNode::Let {body, ..} | ASTNode::Print(body, _) => takes_arguments(body),
+		_ => false,
+	}
+}
+
+// the tokens the formatter writes the way they were, unlike parentheses and periods, which it adds and leaves out
+fn is_kept(token: &Token) -> bool {
+	!matches!(token, Token::OpenParen | Token::CloseParen | Token::Period | Token::Quote)
+}
+
+// numbers are written back in their shortest form
+fn is_same(token: &Token, other: &Token) -> bool {
+	matches!((token, other), (Token::Integer(_), Token::Integer(_))) || token == other
+}
+
+fn line_of(source: &str, offset: usize) -> usize {
+	source[..offset.min(source.len())].matches('\n').count() + 1
+}
+
+struct Formatter<'a> {
+	source: &'a str,
+	// where every token starts, the kept ones, and every `{` with the `}` closing it
+	starts: Vec<usize>,
+	kept: Vec<(Token, Span)>,
+	braces: Vec<(usize, usize)>,
+	// every comment, and whether code comes before it on its line
+	comments: Vec<(Comment, bool)>,
+	next: usize,
+	// the comments inside the statement being written, with code on both sides of them
+	inner: Vec<(Comment, bool)>,
+	// the line of the source the last statement or comment written on its own ended on
+	line: usize,
+}
+
+impl Formatter<'_> {
+	// writes the comments left before `offset`, on their own lines at `depth`, or after
+	// the last line written when code came before them in the source. The ones inside
+	// an expression are kept for `attach`, once the whole statement is written
+	fn comments(&mut self, offset: usize, depth: usize, out: &mut String) {
+		while let Some((comment, trailing)) = self.comments.get(self.next).filter(|(comment, _): &&(Comment, bool)| comment.span.start < offset) {
+			let code_between: bool = self.starts.partition_point(|&start: &usize| start < comment.span.start)
+				!= self.starts.partition_point(|&start: &usize| start < offset);
+
+			if code_between {
+				self.inner.push((comment.clone(), *trailing));
+				self.next += 1;
+				continue;
+			}
+
+			if *trailing && out.ends_with('\n') {
+				out.pop();
+				*out += format!(" {}\n", comment.text).as_str();
+			} else {
+				if depth == 0 && !out.is_empty() && comment.span.line > self.line + 1 {
+					out.push('\n');
+				}
+
+				*out += format!("{}{}\n", "\t".repeat(depth), comment.text).as_str();
+			}
+
+			if depth == 0 {
+				self.line = self.line.max(line_of(self.source, comment.span.end));
+			}
+
+			self.next += 1;
+		}
+	}
+
+	// writes the comments inside the statement written as `text`, which starts at `start` in the source,
+	// after the token they followed there. The lines they end are continued one tab deeper
+	fn attach(&mut self, mut text: String, start: usize) -> String {
+		let written: Vec<(Token, Span)> = lexer::lex_with_comments(text.as_str(), &mut vec![]).unwrap_or_default();
+		let kept: Vec<usize> = (0..written.len()).filter(|&index: &usize| is_kept(&written[index].0)).collect();
+		let first: usize = self.kept.partition_point(|(_, span): &(Token, Span)| span.start < start);
+
+		for (comment, trailing) in mem::take(&mut self.inner).into_iter().rev() {
+			let last: usize = self.kept.partition_point(|(_, span): &(Token, Span)| span.start < comment.span.start);
+			// the code written another way, like `λ(a b).` as `λab.`, takes the comments inside it before itself
+			let before: usize = self.kept[first..last.max(first)]
+				.iter()
+				.zip(&kept)
+				.take_while(|((token, _), &index): &(&(Token, Span), &usize)| is_same(token, &written[index].0))
+				.count();
+
+			// right after the last of those tokens, and the parentheses and periods closing after it
+			let mut offset: usize = 0;
+			if let Some(last) = before.checked_sub(1) {
+				let index: usize = kept[last];
+				let closing: usize = written[index + 1..]
+					.iter()
+					.take_while(|(token, _): &&(Token, Span)| matches!(token, Token::CloseParen | Token::Period))
+					.count();
+
+				offset = written[index + closing].1.end;
+			}
+
+			let after: String = text.split_off(offset).trim_start_matches(' ').to_owned();
+			text.truncate(text.trim_end_matches(' ').len());
+			let line: String = text[text.rfind('\n').map_or(0, |newline: usize| newline + 1)..].to_owned();
+			let indent: String = "\t".repeat(line.len() - line.trim_start_matches('\t').len() + 1);
+			let ends_line: bool = after.is_empty() || after.starts_with('\n');
+			let block: bool = comment.text.starts_with("##");
+
+			text += if line.trim().is_empty() {
+				if block { format!("{} ", comment.text) } else { format!("{}\n{line}", comment.text) }
+			} else if ends_line {
+				format!(" {}", comment.text)
+			} else if block {
+				format!(" {} ", comment.text)
+			} else if trailing {
+				format!(" {}\n{indent}", comment.text)
+			} else {
+				format!("\n{indent}{}\n{indent}", comment.text)
+			}.as_str();
+			text += after.as_str();
+		}
+
+		text
+	}
+
+	fn grouped(&mut self, node: &ASTNode, depth: usize) -> (String, Ending) {
+		let (text, _): (String, Ending) = self.node(node, Place::Statement, depth);
+		(format!("({text})"), Ending::Closed)
+	}
+
+	fn node(&mut self, node: &ASTNode, place: Place, depth: usize) -> (String, Ending) {
+		match node {
+			ASTNode::Nothing => (String::new(), Ending::Closed),
+			ASTNode::Value(value) => self.value(value, place, depth),
+
+			ASTNode::Array(items, _) => {
+				let items: Vec<String> = items
+					.iter()
+					.map(|item: &ASTNode| self.node(item, Place::Argument, depth).0)
+					.collect();
+
+				(format!("'({})", items.join(" ")), Ending::Closed)
+			}
+
+			ASTNode::Definition {name, value, ..} if place == Place::Statement => {
+				let (value, ending): (String, Ending) = self.node(value, Place::Open, depth);
+				(format!("{name} = {value}"), ending)
+			}
+
+			ASTNode::Let {name, value, body} if !place.calls() => {
+				let (value, _): (String, Ending) = self.node(value, Place::Open, depth);
+				let (body, ending): (String, Ending) = self.node(body, Place::Open, depth);
+				(format!("let {name} = {value} in {body}"), ending)
+			}
+
+			ASTNode::Switch {compared, cases} if matches!(place, Place::Statement | Place::Open | Place::Action) => {
+				self.switch(compared, cases, place, depth)
+			}
+
+			ASTNode::Print(value, _) if !place.calls() => {
+				let (value, ending): (String, Ending) = self.node(value, Place::Open, depth);
+				(format!("! {value}"), ending)
+			}
+
+			ASTNode::Import {path, namespace, ..} if place == Place::Statement => {
+				let namespace: String = namespace.as_ref().map_or_else(String::new, |namespace: &String| format!(" as {namespace}"));
+				(format!("import {path:?}{namespace}"), Ending::Closed)
+			}
+
+			ASTNode::Input {numeric, ..} => {
+				(if *numeric { "??" } else { "?" }.to_owned(), Ending::Closed)
+			}
+
+			ASTNode::Length(value, _) => (format!("|{}|", self.node(value, Place::Open, depth).0), Ending::Closed),
+			ASTNode::IntegerPart(value, _) => (format!("[{}]", self.node(value, Place::Open, depth).0), Ending::Closed),
+			ASTNode::RationalPart(value, _) => (format!("{{{}}}", self.node(value, Place::Open, depth).0), Ending::Closed),
+
+			ASTNode::Call {name, args, ..} => {
+				let args: Vec<String> = args
+					.iter()
+					.map(|arg: &ASTNode| self.node(arg, Place::Argument, depth).0)
+					.collect();
+
+				(format!("({name} {})", args.join(" ")), Ending::Closed)
+			}
+
+			// the arguments are parsed like statements, so the ones taking arguments themselves
+			// need parentheses unless they are the last
+			ASTNode::LambdaCall {lambda, args, ..} => {
+				let lambda: String = self.callee(lambda, depth);
+				let mut written: Vec<String> = vec![];
+				for (index, arg) in args.iter().enumerate() {
+					let takes: bool = index + 1 < args.len() && takes_arguments(arg);
+					written.push(if takes { self.grouped(arg, depth).0 } else { self.node(arg, Place::Open, depth).0 });
+				}
+
+				(format!("({lambda} {})", written.join(" ")), Ending::Closed)
+			}
+
+			ASTNode::Operation {left, operation, right, ..} if place != Place::Operand => {
+				self.operation(left, operation, right, place, depth)
+			}
+
+			_ => self.grouped(node, depth),
+		}
+	}
+
+	fn value(&mut self, value: &Value, place: Place, depth: usize) -> (String, Ending) {
+		match value {
+			Value::Variable(name, _) => (name.clone(), if place.calls() { Ending::Closed } else { Ending::Open }),
+			Value::Lambda {args_def, content, ..} => self.lambda(args_def, content, place, depth),
+			other => (literal(other), Ending::Closed),
+		}
+	}
+
+	fn lambda(&mut self, params: &[Parameter], content: &ASTNode, place: Place, depth: usize) -> (String, Ending) {
+		match sugar(params, content) {
+			Some(Sugar::IntegerPart) => return ("[]".to_owned(), Ending::Closed),
+			Some(Sugar::RationalPart) => return ("{}".to_owned(), Ending::Closed),
+			Some(Sugar::Print) if place.calls() => return ("!".to_owned(), Ending::Closed),
+			Some(Sugar::Section(left, op, right)) => return (self.section(left, op, right, depth), Ending::Closed),
+			Some(Sugar::Print) | None => {}
+		}
+
+		let params: String = parser::parameters(params);
+		if place.calls() {
+			let (body, _): (String, Ending) = self.node(content, Place::Open, depth);
+			return (format!("(λ{params}. {body})"), Ending::Closed);
+		}
+
+		let (body, ending): (String, Ending) = self.node(content, Place::Open, depth);
+		(format!("λ{params}. {body}"), ending)
+	}
+
+	fn section(&mut self, left: Option<&ASTNode>, op: &Operation, right: Option<&ASTNode>, depth: usize) -> String {
+		let symbol: &str = symbol(op);
+		match (left, right) {
+			(None, None) => format!("({symbol})"),
+			(None, Some(right)) => format!("({symbol} {})", self.right(op, right, Place::Chain, depth).0),
+			(Some(left), _) => format!("({} {symbol})", self.left(left, op, Place::Open, depth)),
+		}
+	}
+
+	// a lambda called right where it is written has to be in parentheses to take the arguments
+	fn callee(&mut self, lambda: &Value, depth: usize) -> String {
+		let section: bool = matches!(lambda, Value::Lambda {args_def, content, ..}
+			if matches!(sugar(args_def, content), Some(Sugar::Section(..))));
+
+		let (text, _): (String, Ending) = self.value(lambda, Place::Statement, depth);
+		if section {
+			text
+		} else {
+			format!("({text})")
+		}
+	}
+
+	fn operation(&mut self, left: &ASTNode, op: &Operation, right: &ASTNode, place: Place, depth: usize) -> (String, Ending) {
+		let (place, separator): (Place, &str) = match place {
+			Place::Statement => (Place::Open, " "),
+			Place::Action => (Place::Chain, " "),
+			Place::Argument => (Place::Argument, ""),
+			_ => (place, " "),
+		};
+
+		let left: String = self.left(left, op, place, depth);
+		let (right, ending): (String, Ending) = self.right(op, right, if separator.is_empty() { Place::Argument } else { Place::Chain }, depth);
+		(format!("{left}{separator}{}{separator}{right}", symbol(op)), ending)
+	}
+
+	// the left side is parsed first and the operation after it, which only
+	// keeps it whole when it is a single value or an operation that continues
+	fn left(&mut self, node: &ASTNode, op: &Operation, place: Place, depth: usize) -> String {
+		let bare: bool = match node {
+			ASTNode::Operation {operation, ..} => repeats(operation)
+				|| (!chains(operation) && matches!(op, Operation::And | Operation::Or)),
+
+			// `x = 1` would be a definition
+			ASTNode::Value(Value::Variable(..)) => place.calls() || *op != Operation::Equal,
+			// `()` is parsed without looking for an operation after it
+			ASTNode::Value(Value::None) => false,
+			ASTNode::Value(Value::Lambda {args_def, content, ..}) => matches!(sugar(args_def, content), Some(Sugar::Section(..))),
+			ASTNode::Value(_)
+			| ASTNode::Array(..)
+			| ASTNode::Input {..}
+			| ASTNode::Length(..)
+			| ASTNode::IntegerPart(..)
+			| ASTNode::RationalPart(..)
+			| ASTNode::Call {..}
+			| ASTNode::LambdaCall {..} => true,
+			_ => false,
+		};
+
+		if bare {
+			self.node(node, place, depth).0
+		} else {
+			self.grouped(node, depth).0
+		}
+	}
+
+	fn right(&mut self, op: &Operation, node: &ASTNode, place: Place, depth: usize) -> (String, Ending) {
+		self.node(node, if chains(op) { place } else { Place::Operand }, depth)
+	}
+
+	fn pattern(&mut self, pattern: &Pattern, depth: usize) -> String {
+		match pattern {
+			Pattern::Wildcard => "_".to_owned(),
+			Pattern::Binding(name) => name.clone(),
+			Pattern::Literal(value) => literal(value),
+			Pattern::Range(from, to) => format!("{}..{}", literal(from), literal(to)),
+			// parsed like a guard
+			Pattern::Pinned(pin) => format!("={}", self.node(pin, Place::Chain, depth).0),
+			Pattern::Cons(head, tail) => format!("({} . {})", self.pattern(head, depth), self.pattern(tail, depth)),
+			Pattern::Array(items) => {
+				let items: Vec<String> = items.iter().map(|item: &Pattern| self.pattern(item, depth)).collect();
+				format!("'({})", items.join(" "))
+			}
+		}
+	}
+
+	fn switch(&mut self, compared: &ASTNode, cases: &[Case], place: Place, depth: usize) -> (String, Ending) {
+		// only these look for a `$` after themselves
+		let bare: bool = match compared {
+			ASTNode::Value(Value::Variable(..))
+			| ASTNode::Length(..)
+			| ASTNode::IntegerPart(..)
+			| ASTNode::Call {..}
+			| ASTNode::LambdaCall {..} => true,
+			ASTNode::Value(Value::Lambda {args_def, content, ..}) => matches!(sugar(args_def, content), Some(Sugar::Section(..))),
+			ASTNode::Value(Value::None) => false,
+			ASTNode::Value(_) | ASTNode::Array(..) | ASTNode::Input {..} => !place.calls(),
+			_ => false,
+		};
+
+		let compared: String = if bare {
+			self.node(compared, if place.calls() { Place::Operand } else { Place::Open }, depth).0
+		} else {
+			self.grouped(compared, depth).0
+		};
+
+		if cases.is_empty() {
+			return (format!("{compared} ${{}}"), Ending::Closed);
+		}
+
+		let heads: Vec<String> = cases
+			.iter()
+			.map(|case: &Case| {
+				let pattern: String = self.pattern(&case.pattern, depth + 1);
+				match &case.guard {
+					Some(guard) => format!("{pattern} when {}", self.node(guard, Place::Chain, depth + 1).0),
+					None => pattern,
+				}
+			}).collect();
+
+		let width: usize = heads
+			.iter()
+			.filter(|head: &&String| !head.contains('\n'))
+			.map(|head: &String| head.chars().count())
+			.max()
+			.unwrap_or(0);
+
+		let mut out: String = format!("{compared} ${{\n");
+		for (case, head) in cases.iter().zip(heads) {
+			self.comments(case.span.start, depth + 1, &mut out);
+
+			let (action, _): (String, Ending) = self.node(&case.action, Place::Action, depth + 1);
+			let padding: usize = width.saturating_sub(head.chars().count());
+			out += format!("{}{head}{} -> {action}\n", "\t".repeat(depth + 1), " ".repeat(padding)).as_str();
+		}
+
+		// the `{` of the switch is the last one before its first case
+		let close: Option<usize> = self.braces
+			.iter()
+			.filter(|(open, _): &&(usize, usize)| *open < cases[0].span.start)
+			.max_by_key(|(open, _): &&(usize, usize)| *open)
+			.map(|(_, close): &(usize, usize)| *close);
+
+		if let Some(close) = close {
+			self.comments(close, depth + 1, &mut out);
+		}
+
+		out += format!("{}}}", "\t".repeat(depth)).as_str();
+		(out, Ending::Closed)
+	}
+}
+
+// nodes are compared without their spans, so only what the code does is left
+fn shape(statements: &[(ASTNode, Span)]) -> Vec<&ASTNode> {
+	statements.iter().map(|(node, _): &(ASTNode, Span)| node).collect()
+}
+
+/// Writes `source` back the canonical way: `λ` and `->`, one statement per line, switch cases indented with tabs and their arrows aligned,
+/// parentheses only where they are needed and comments where they were.
+pub fn format(source: &str) -> Result<String, LambisError> {
+	let mut comments: Vec<Comment> = vec![];
+	let tokens: Vec<(Token, Span)> = lexer::lex_with_comments(source, &mut comments)?;
+	let comments: Vec<(Comment, bool)> = comments
+		.into_iter()
+		.map(|comment: Comment| {
+			let before: usize = tokens.partition_point(|(_, span): &(Token, Span)| span.start < comment.span.start);
+			let trailing: bool = before > 0 && line_of(source, tokens[before - 1].1.end) == comment.span.line;
+			(comment, trailing)
+		}).collect();
+
+	let starts: Vec<usize> = tokens.iter().map(|(_, span): &(Token, Span)| span.start).collect();
+	let kept: Vec<(Token, Span)> = tokens.iter().filter(|(token, _): &&(Token, Span)| is_kept(token)).cloned().collect();
+	let mut braces: Vec<(usize, usize)> = vec![];
+	let mut open: Vec<usize> = vec![];
+	for (token, span) in &tokens {
+		match token {
+			Token::OpenBrace => open.push(span.start),
+			Token::CloseBrace => braces.extend(open.pop().map(|start: usize| (start, span.start))),
+			_ => {}
+		}
+	}
+
+	let statements: Vec<(ASTNode, Span)> = parser::parse_statements(tokens)?;
+	let mut formatter: Formatter = Formatter {
+		source,
+		starts,
+		kept,
+		braces,
+		comments,
+		next: 0,
+		inner: vec![],
+		line: 0,
+	};
+
+	let mut out: String = String::new();
+	for (node, span) in &statements {
+		formatter.comments(span.start, 0, &mut out);
+
+		if !out.is_empty() && span.line > formatter.line + 1 {
+			out.push('\n');
+		}
+
+		let start: usize = out.len();
+		let (text, ending): (String, Ending) = formatter.node(node, Place::Statement, 0);
+		out += text.as_str();
+		// definitions always end with one, the rest only when they take arguments
+		if ending == Ending::Open || matches!(node, ASTNode::Definition {..}) {
+			out.push('.');
+		}
+
+		let end: usize = out.len();
+		out.push('\n');
+		formatter.line = line_of(source, span.end);
+		formatter.comments(span.end, 0, &mut out);
+
+		let rest: String = out.split_off(end);
+		let statement: String = out.split_off(start);
+		out += formatter.attach(statement, span.start).as_str();
+		out += rest.as_str();
+	}
+
+	formatter.comments(usize::MAX, 0, &mut out);
+
+	let formatted: Vec<(ASTNode, Span)> = lexer::lex(out.as_str())
+		.and_then(parser::parse_statements)
+		.unwrap_or_default();
+
+	if shape(&formatted) != shape(&statements) {
+		let changed: usize = (0..statements.len())
+			.find(|&index: &usize| formatted.get(index..=index).is_none_or(|written: &[(ASTNode, Span)]| shape(written) != shape(&statements[index..=index])))
+			.unwrap_or(0);
+
+		return Err(LambisError::parse("this can't be formatted without changing what it does", statements[changed].1)
+			.with_hint("please report it together with the code"));
+	}
+
+	Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn statements(source: &str) -> Vec<(ASTNode, Span)> {
+		parser::parse_statements(lexer::lex(source).unwrap()).unwrap()
+	}
+
+	#[test]
+	fn shapes_only_differ_in_what_the_code_does() {
+		let source: &str = "f = λx. x ${\n\t0 when x = 0 -> '(x 1)\n\t_ -> (f x-1)\n}.\n";
+		let respaced: &str = "\n\nf = \\x. x ${ 0 when x=0 -> '(x 1) _ -> (f x - 1) }.\n";
+		assert_ne!(statements(source), statements(respaced));
+		assert_eq!(shape(&statements(source)), shape(&statements(respaced)));
+		assert_ne!(shape(&statements(source)), shape(&statements("f = λx. x ${\n\t0 when x = 0 -> '(x 2)\n\t_ -> (f x-1)\n}.\n")));
+	}
+
+	// code written every way the parser takes, and every example
+	fn sources() -> Vec<String> {
+		let mut sources: Vec<String> = [
+			"first = (λx. x !! 0).\nrest  = λx. (drop 1 x).",
+			"inc=\\x.x+1\n!(inc 2)",
+			"f=λ(list fn).list ${ () -> '() (h . t) -> (fn h):(f t fn) }.",
+			"g = (2-).\nh = (* 3).\n! (g (h 10))",
+			"xs = '(1 2 '(3 4) \"a\" 'b)\n! |xs|",
+			"h = λx. let y = x*2 in y+1\nk = λx. x%2 = 0 && x > 10 || x = 3",
+			"p = λx. x ${ 0 -> 1 # zero\n _ -> x * (p x-1)\n # end\n}.",
+			"# a\nx = \"a\".\n\n\n## b ##\ny = [x] + {x} # trailing\n",
+			"d = λn. n ${ 1..9 -> 'digit n when n < 0 -> 'negative _ -> 'big }.",
+			"if = λc~t~f. c ${ true -> t false -> f }.\n(if (not false) (! 1) (! 2))",
+			"s = λ(x y). x ${\n=y -> 'same\n-1..1 -> 'near\n'(=(y + 1) _) -> 'next\n_ -> 'other\n}.",
+			"z = '(1 # one\n 2 ## two ## 3)\ng = λx. (f x # end\n).\nf = λab. # body\n a + b\nh = λ(a b).\n# own line\na*b",
+			"f = λx. x ${\n\t0 when x = # guard\n1 -> 1\n\t_ -> (g x) # after g\n\t\t+ 1\n}.\nx = 1 # a\n## b ## + # c\n2",
+		].map(str::to_owned).to_vec();
+
+		let examples: std::path::PathBuf = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("examples");
+		for entry in std::fs::read_dir(examples).unwrap() {
+			sources.push(std::fs::read_to_string(entry.unwrap().path()).unwrap());
+		}
+
+		sources
+	}
+
+	#[test]
+	fn formatting_is_idempotent() {
+		for source in sources() {
+			let formatted: String = format(source.as_str()).unwrap();
+			assert_eq!(format(formatted.as_str()).unwrap(), formatted, "formatting changed its own output for:\n{source}");
+		}
+	}
+
+	#[test]
+	fn formatting_keeps_what_the_code_does() {
+		for source in sources() {
+			let formatted: String = format(source.as_str()).unwrap();
+			assert_eq!(shape(&statements(formatted.as_str())), shape(&statements(source.as_str())), "formatting changed:\n{source}");
+		}
+	}
+
+	#[test]
+	fn comments_between_cases_stay_in_the_switch() {
+		let source: &str = "f = λx. x ${ # opening\n\t0 -> 1 # zero\n\t_ -> x ${\n\t\t1 -> 2\n\t\t# inner\n\t}\n\t# outer\n}.\n! (f 0)\n";
+		assert_eq!(format(source).unwrap(), source);
+	}
+
+	#[test]
+	fn comments_inside_expressions_stay_after_their_code() {
+		let cases: [(&str, &str); 6] = [
+			("x = 1 + # inside\n2\n", "x = 1 + # inside\n\t2.\n"),
+			("x = 1 +\n# own line\n2\n", "x = 1 +\n\t# own line\n\t2.\n"),
+			("(f 1 ## inline ## 2)\n", "(f 1 ## inline ## 2)\n"),
+			("f = λx. x ${\n\t0 when x = # guard\n1 -> 1\n}.\n", "f = λx. x ${\n\t0 when x = # guard\n\t\t1 -> 1\n}.\n"),
+			("y = (f 1) # note\n + 2\n", "y = (f 1) # note\n\t+ 2.\n"),
+			// the parameters are written another way, so the comment goes before them
+			("f = λ(a b). # c\n a + b\n", "f = λ # c\n\tab. a + b.\n"),
+		];
+
+		for (source, formatted) in cases {
+			assert_eq!(format(source).unwrap(), formatted);
+			assert_eq!(format(formatted).unwrap(), formatted);
+		}
+	}
+
+	#[test]
+	fn definitions_always_end_with_a_period() {
+		let source: &str = "even = λn. n % 2 = 0\nx = |'(1 2)|\ny = 5\nz = 1.5\n! (even y)\n";
+		assert_eq!(format(source).unwrap(), "even = λn. n % 2 = 0.\nx = |'(1 2)|.\ny = 5.\nz = 1.5.\n! (even y)\n");
+	}
+}
@@ -0,0 +1,423 @@
+use std::{
+	cmp::Ordering,
+	fmt,
+	ops::{Add, Mul, Neg, Sub},
+};
+
+// every limb holds 9 decimal digits, so printing and parsing stay trivial
+const BASE: u64 = 1_000_000_000;
+const DIGITS: usize = 9;
+
+// sign and magnitude, limbs are little-endian and never end with a zero limb.
+// zero has no limbs and is never negative, so derived equality is numeric equality
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct BigInt {
+	negative: bool,
+	limbs: Vec<u32>,
+}
+
+fn trim(limbs: &mut Vec<u32>) {
+	while limbs.last() == Some(&0) {
+		limbs.pop();
+	}
+}
+
+fn compare(a: &[u32], b: &[u32]) -> Ordering {
+	a.len()
+		.cmp(&b.len())
+		.then_with(|| a.iter().rev().cmp(b.iter().rev()))
+}
+
+fn add(a: &[u32], b: &[u32]) -> Vec<u32> {
+	let mut result: Vec<u32> = Vec::with_capacity(a.len().max(b.len()) + 1);
+	let mut carry: u64 = 0;
+
+	for i in 0..a.len().max(b.len()) {
+		let sum: u64 = u64::from(*a.get(i).unwrap_or(&0)) + u64::from(*b.get(i).unwrap_or(&0)) + carry;
+		result.push((sum % BASE) as u32);
+		carry = sum / BASE;
+	}
+
+	if carry > 0 {
+		result.push(carry as u32);
+	}
+
+	result
+}
+
+// `a` must not be smaller than `b`
+fn sub(a: &[u32], b: &[u32]) -> Vec<u32> {
+	let mut result: Vec<u32> = Vec::with_capacity(a.len());
+	let mut borrow: i64 = 0;
+
+	for (i, &limb) in a.iter().enumerate() {
+		let mut difference: i64 = i64::from(limb) - i64::from(*b.get(i).unwrap_or(&0)) - borrow;
+		borrow = i64::from(difference < 0);
+		if difference < 0 {
+			difference += BASE as i64;
+		}
+
+		result.push(difference as u32);
+	}
+
+	trim(&mut result);
+	result
+}
+
+fn mul(a: &[u32], b: &[u32]) -> Vec<u32> {
+	if a.is_empty() || b.is_empty() {
+		return vec![];
+	}
+
+	let mut result: Vec<u64> = vec![0; a.len() + b.len()];
+	for (i, &x) in a.iter().enumerate() {
+		let mut carry: u64 = 0;
+		for (j, &y) in b.iter().enumerate() {
+			let current: u64 = result[i + j] + u64::from(x) * u64::from(y) + carry;
+			result[i + j] = current % BASE;
+			carry = current / BASE;
+		}
+
+		result[i + b.len()] += carry;
+	}
+
+	let mut result: Vec<u32> = result.into_iter().map(|limb: u64| limb as u32).collect();
+	trim(&mut result);
+	result
+}
+
+fn mul_small(a: &[u32], factor: u32) -> Vec<u32> {
+	mul(a, &[factor])
+}
+
+// schoolbook long division, every quotient limb is estimated from the
+// leading limbs of the remainder and the divisor, then corrected
+fn div_rem(a: &[u32], b: &[u32]) -> (Vec<u32>, Vec<u32>) {
+	if let [divisor] = b {
+		let divisor: u64 = u64::from(*divisor);
+		let mut quotient: Vec<u32> = vec![0; a.len()];
+		let mut remainder: u64 = 0;
+
+		for i in (0..a.len()).rev() {
+			let current: u64 = remainder * BASE + u64::from(a[i]);
+			quotient[i] = (current / divisor) as u32;
+			remainder = current % divisor;
+		}
+
+		trim(&mut quotient);
+		let mut remainder: Vec<u32> = vec![remainder as u32];
+		trim(&mut remainder);
+		return (quotient, remainder);
+	}
+
+	let leading = |limbs: &[u32], from: usize| -> u128 {
+		limbs.iter().skip(from).rev().fold(0, |acc: u128, &limb: &u32| acc * u128::from(BASE) + u128::from(limb))
+	};
+
+	let from: usize = b.len() - 2;
+	let divisor: u128 = leading(b, from);
+	let mut quotient: Vec<u32> = vec![0; a.len()];
+	let mut remainder: Vec<u32> = vec![];
+
+	for i in (0..a.len()).rev() {
+		remainder.insert(0, a[i]);
+		trim(&mut remainder);
+		if compare(&remainder, b) == Ordering::Less {
+			continue;
+		}
+
+		let mut estimate: u32 = (leading(&remainder, from) / divisor).min(u128::from(BASE - 1)) as u32;
+		while estimate > 0 && compare(&mul_small(b, estimate), &remainder) == Ordering::Greater {
+			estimate -= 1;
+		}
+
+		while u64::from(estimate) < BASE - 1 && compare(&mul_small(b, estimate + 1), &remainder) != Ordering::Greater {
+			estimate += 1;
+		}
+
+		remainder = sub(&remainder, &mul_small(b, estimate));
+		quotient[i] = estimate;
+	}
+
+	trim(&mut quotient);
+	(quotient, remainder)
+}
+
+impl BigInt {
+	fn new(negative: bool, mut limbs: Vec<u32>) -> Self {
+		trim(&mut limbs);
+		Self {
+			negative: negative && !limbs.is_empty(),
+			limbs,
+		}
+	}
+
+	// accepts an optional `-` followed by decimal digits
+	pub fn parse(source: &str) -> Option<Self> {
+		let (negative, digits): (bool, &str) = match source.strip_prefix('-') {
+			Some(digits) => (true, digits),
+			None => (false, source),
+		};
+
+		if digits.is_empty() || !digits.bytes().all(|byte: u8| byte.is_ascii_digit()) {
+			return None;
+		}
+
+		let limbs: Vec<u32> = digits
+			.as_bytes()
+			.rchunks(DIGITS)
+			.map(|chunk: &[u8]| chunk.iter().fold(0, |acc: u32, &digit: &u8| acc * 10 + u32::from(digit - b'0')))
+			.collect();
+
+		Some(Self::new(negative, limbs))
+	}
+
+	// only finite numbers have an integer value, fractions are truncated
+	pub fn from_f64(number: f64) -> Option<Self> {
+		number.is_finite().then(|| format!("{:.0}", number.trunc())).and_then(|digits: String| Self::parse(digits.as_str()))
+	}
+
+	pub fn is_zero(&self) -> bool {
+		self.limbs.is_empty()
+	}
+
+	pub fn is_negative(&self) -> bool {
+		self.negative
+	}
+
+	pub fn to_i128(&self) -> Option<i128> {
+		self.limbs.iter().rev().try_fold(0, |acc: i128, &limb: &u32| {
+			let shifted: i128 = acc.checked_mul(BASE as i128)?;
+			if self.negative {
+				shifted.checked_sub(i128::from(limb))
+			} else {
+				shifted.checked_add(i128::from(limb))
+			}
+		})
+	}
+
+	pub fn to_f64(&self) -> f64 {
+		self.to_string().parse::<f64>().unwrap_or(f64::NAN)
+	}
+
+	pub fn abs(&self) -> Self {
+		Self::new(false, self.limbs.clone())
+	}
+
+	pub fn gcd(&self, other: &Self) -> Self {
+		let (mut a, mut b): (Self, Self) = (self.abs(), other.abs());
+		while let Some((_, remainder)) = a.div_rem(&b) {
+			(a, b) = (b, remainder);
+		}
+
+		a
+	}
+
+	// the number of decimal digits, without the sign
+	pub fn digits(&self) -> usize {
+		self.limbs.last().map_or(0, |last: &u32| (self.limbs.len() - 1) * DIGITS + last.to_string().len())
+	}
+
+	// an estimate good enough to tell how big a result will get
+	pub fn log10(&self) -> f64 {
+		self.limbs.last().map_or(f64::NEG_INFINITY, |&last: &u32| {
+			((self.limbs.len() - 1) * DIGITS) as f64 + f64::from(last).log10()
+		})
+	}
+
+	pub fn pow(&self, mut exponent: u32) -> Self {
+		let mut result: Self = Self::from(1);
+		let mut base: Self = self.clone();
+
+		while exponent > 0 {
+			if exponent & 1 == 1 {
+				result = &result * &base;
+			}
+
+			exponent >>= 1;
+			if exponent > 0 {
+				base = &base * &base;
+			}
+		}
+
+		result
+	}
+
+	pub fn ten_to(exponent: usize) -> Self {
+		let mut limbs: Vec<u32> = vec![0; exponent / DIGITS];
+		limbs.push(10u32.pow((exponent % DIGITS) as u32));
+		Self::new(false, limbs)
+	}
+
+	// truncating division like `i128`, `None` when dividing by zero
+	pub fn div_rem(&self, other: &Self) -> Option<(Self, Self)> {
+		if other.is_zero() {
+			return None;
+		}
+
+		let (quotient, remainder): (Vec<u32>, Vec<u32>) = div_rem(&self.limbs, &other.limbs);
+		Some((
+			Self::new(self.negative != other.negative, quotient),
+			Self::new(self.negative, remainder),
+		))
+	}
+}
+
+impl From<i128> for BigInt {
+	fn from(number: i128) -> Self {
+		let mut magnitude: u128 = number.unsigned_abs();
+		let mut limbs: Vec<u32> = vec![];
+
+		while magnitude > 0 {
+			limbs.push((magnitude % u128::from(BASE)) as u32);
+			magnitude /= u128::from(BASE);
+		}
+
+		Self::new(number < 0, limbs)
+	}
+}
+
+impl Neg for &BigInt {
+	type Output = BigInt;
+
+	fn neg(self) -> BigInt {
+		BigInt::new(!self.negative, self.limbs.clone())
+	}
+}
+
+impl Add for &BigInt {
+	type Output = BigInt;
+
+	fn add(self, other: &BigInt) -> BigInt {
+		if self.negative == other.negative {
+			return BigInt::new(self.negative, add(&self.limbs, &other.limbs));
+		}
+
+		match compare(&self.limbs, &other.limbs) {
+			Ordering::Less => BigInt::new(other.negative, sub(&other.limbs, &self.limbs)),
+			_ => BigInt::new(self.negative, sub(&self.limbs, &other.limbs)),
+		}
+	}
+}
+
+impl Sub for &BigInt {
+	type Output = BigInt;
+
+	fn sub(self, other: &BigInt) -> BigInt {
+		self + &-other
+	}
+}
+
+impl Mul for &BigInt {
+	type Output = BigInt;
+
+	fn mul(self, other: &BigInt) -> BigInt {
+		BigInt::new(self.negative != other.negative, mul(&self.limbs, &other.limbs))
+	}
+}
+
+impl Ord for BigInt {
+	fn cmp(&self, other: &Self) -> Ordering {
+		match (self.negative, other.negative) {
+			(false, true) => Ordering::Greater,
+			(true, false) => Ordering::Less,
+			(false, false) => compare(&self.limbs, &other.limbs),
+			(true, true) => compare(&other.limbs, &self.limbs),
+		}
+	}
+}
+
+impl PartialOrd for BigInt {
+	fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+		Some(self.cmp(other))
+	}
+}
+
+impl fmt::Display for BigInt {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		let Some((last, rest)) = self.limbs.split_last() else {
+			return write!(f, "0");
+		};
+
+		if self.negative {
+			write!(f, "-")?;
+		}
+
+		write!(f, "{last}")?;
+		for limb in rest.iter().rev() {
+			write!(f, "{limb:09}")?;
+		}
+
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::BigInt;
+
+	fn big(source: &str) -> BigInt {
+		BigInt::parse(source).unwrap()
+	}
+
+	#[test]
+	fn carries_across_limbs() {
+		assert_eq!(&big("999999999") + &big("1"), big("1000000000"));
+		assert_eq!(&big("999999999999999999999999999") + &big("1"), big("1000000000000000000000000000"));
+		assert_eq!(&big("1000000000000000000") - &big("1"), big("999999999999999999"));
+		assert_eq!(&big("-1000000000") + &big("1"), big("-999999999"));
+		assert_eq!(&big("999999999") * &big("999999999"), big("999999998000000001"));
+		assert_eq!(&big("999999999999999999") * &big("999999999999999999"), big("999999999999999998000000000000000001"));
+		assert_eq!(big("1000000000").to_string(), "1000000000");
+		assert_eq!(big("-000000001000000007").to_string(), "-1000000007");
+	}
+
+	#[test]
+	fn division_truncates_like_i128() {
+		let values: [i128; 10] = [7, -7, 2, -2, 1_000_000_007, -999_999_999_999, 10i128.pow(30) + 7, -(10i128.pow(27) + 3), i128::MAX, i128::MIN + 1];
+		for a in values {
+			for b in values {
+				let (quotient, remainder): (BigInt, BigInt) = BigInt::from(a).div_rem(&BigInt::from(b)).unwrap();
+				assert_eq!((quotient, remainder), (BigInt::from(a / b), BigInt::from(a % b)), "{a} / {b}");
+			}
+		}
+	}
+
+	#[test]
+	fn division_of_big_numbers() {
+		let a: BigInt = big("-123456789012345678901234567890123456789012345678901234567890");
+		for b in ["1000000000", "-999999999999999999", "340282366920938463463374607431768211457", "-1"] {
+			let b: BigInt = big(b);
+			let (quotient, remainder): (BigInt, BigInt) = a.div_rem(&b).unwrap();
+			assert_eq!(&(&quotient * &b) + &remainder, a);
+			assert!(remainder.abs() < b.abs());
+			assert!(remainder.is_zero() || remainder.is_negative() == a.is_negative());
+		}
+
+		assert_eq!(a.div_rem(&BigInt::from(0)), None);
+	}
+
+	#[test]
+	fn i128_round_trip() {
+		for number in [0, 1, -1, 999_999_999, 1_000_000_000, i128::MAX, i128::MIN, i128::MIN + 1] {
+			assert_eq!(BigInt::from(number).to_i128(), Some(number));
+			assert_eq!(big(number.to_string().as_str()), BigInt::from(number));
+		}
+
+		assert_eq!((&BigInt::from(i128::MAX) + &BigInt::from(1)).to_i128(), None);
+		assert_eq!((&BigInt::from(i128::MIN) - &BigInt::from(1)).to_i128(), None);
+	}
+
+	#[test]
+	fn from_f64() {
+		assert_eq!(BigInt::from_f64(2.7), Some(BigInt::from(2)));
+		assert_eq!(BigInt::from_f64(-2.7), Some(BigInt::from(-2)));
+		assert_eq!(BigInt::from_f64(-0.5), Some(BigInt::from(0)));
+		assert!(!BigInt::from_f64(-0.5).unwrap().is_negative());
+		assert_eq!(BigInt::from_f64(1e30), Some(big("1000000000000000019884624838656")));
+		assert_eq!(BigInt::from_f64(f64::MAX).unwrap().digits(), 309);
+		assert_eq!(BigInt::from_f64(f64::INFINITY), None);
+		assert_eq!(BigInt::from_f64(f64::NEG_INFINITY), None);
+		assert_eq!(BigInt::from_f64(f64::NAN), None);
+	}
+}
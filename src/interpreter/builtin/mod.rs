@@ -0,0 +1,197 @@
+use std::{fmt, fs, io::Write, ptr};
+use super::{error::{LambisError, Span}, parser::Value};
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Builtin {
+	ReadFile,
+	ReadLines,
+	WriteFile,
+	AppendFile,
+	Decimal,
+	Not,
+}
+
+impl Builtin {
+	pub const ALL: [Builtin; 6] = [
+		Builtin::ReadFile,
+		Builtin::ReadLines,
+		Builtin::WriteFile,
+		Builtin::AppendFile,
+		Builtin::Decimal,
+		Builtin::Not,
+	];
+
+	pub fn name(self) -> &'static str {
+		match self {
+			Builtin::ReadFile => "fileread",
+			Builtin::ReadLines => "filelines",
+			Builtin::WriteFile => "filewrite",
+			Builtin::AppendFile => "fileappend",
+			Builtin::Decimal => "decimal",
+			Builtin::Not => "not",
+		}
+	}
+
+	pub fn arity(self) -> usize {
+		match self {
+			Builtin::ReadFile | Builtin::ReadLines | Builtin::Decimal | Builtin::Not => 1,
+			Builtin::WriteFile | Builtin::AppendFile => 2,
+		}
+	}
+
+	// the builtins a sandboxed script can't call
+	pub fn touches_files(self) -> bool {
+		matches!(self, Builtin::ReadFile | Builtin::ReadLines | Builtin::WriteFile | Builtin::AppendFile)
+	}
+
+	pub fn call(self, args: Vec<Value>, span: Span) -> Result<Value, LambisError> {
+		match self {
+			Builtin::Decimal => return decimal(args, span),
+			Builtin::Not => return not(args, span),
+			_ => {}
+		}
+
+		let name: &str = self.name();
+		let strings: Vec<String> = args
+			.into_iter()
+			.map(|arg: Value| match arg {
+				Value::String(s) => Ok(s),
+				what => Err(LambisError::runtime(format!("{name}: expected <String>, got {what:?}"), span)),
+			}).collect::<Result<Vec<String>, LambisError>>()?;
+
+		let path: &str = strings[0].as_str();
+		let failed = |error: std::io::Error| -> LambisError {
+			LambisError::runtime(format!("{name}: cannot access «{path}»: {error}"), span)
+		};
+
+		Ok(match self {
+			Builtin::ReadFile => Value::String(fs::read_to_string(path).map_err(failed)?),
+
+			Builtin::ReadLines => Value::Array(fs::read_to_string(path)
+				.map_err(failed)?
+				.lines()
+				.map(|line: &str| Value::String(line.to_owned()))
+				.collect()),
+
+			Builtin::WriteFile => {
+				fs::write(path, strings[1].as_str()).map_err(failed)?;
+				Value::None
+			}
+
+			Builtin::AppendFile => {
+				fs::OpenOptions::new()
+					.create(true)
+					.append(true)
+					.open(path)
+					.and_then(|mut file: fs::File| file.write_all(strings[1].as_bytes()))
+					.map_err(failed)?;
+				Value::None
+			}
+
+			Builtin::Decimal | Builtin::Not => unreachable!("«{name}» doesn't work with files"),
+		})
+	}
+}
+
+// the only lossy conversion between numbers has to be asked for explicitly
+fn decimal(args: Vec<Value>, span: Span) -> Result<Value, LambisError> {
+	Ok(Value::Decimal(match args.into_iter().next() {
+		Some(Value::Integer(n)) => n as f64,
+		Some(Value::BigInt(n)) => n.to_f64(),
+		Some(Value::Rational(n)) => n.to_f64(),
+		Some(Value::Decimal(n)) => n,
+		what => return Err(LambisError::runtime(format!("decimal: expected <Integer|Rational|Decimal>, got {what:?}"), span)),
+	}))
+}
+
+fn not(args: Vec<Value>, span: Span) -> Result<Value, LambisError> {
+	match args.into_iter().next() {
+		Some(Value::Bool(bool)) => Ok(Value::Bool(!bool)),
+		what => Err(LambisError::runtime(format!("not: expected <Bool>, got {what:?}"), span)),
+	}
+}
+
+// a function registered by the program embedding the interpreter
+pub struct Native {
+	pub name: String,
+	pub arity: usize,
+	pub function: Box<dyn Fn(Vec<Value>) -> Result<Value, String>>,
+}
+
+impl Native {
+	pub fn call(&self, args: Vec<Value>, span: Span) -> Result<Value, LambisError> {
+		(self.function)(args).map_err(|message: String| LambisError::runtime(format!("{}: {message}", self.name), span))
+	}
+}
+
+impl fmt::Debug for Native {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.debug_struct("Native")
+			.field("name", &self.name)
+			.field("arity", &self.arity)
+			.finish_non_exhaustive()
+	}
+}
+
+// closures can't be compared, so natives are only equal to themselves
+impl PartialEq for Native {
+	fn eq(&self, other: &Self) -> bool {
+		ptr::eq(self, other)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::{env, fs, path::PathBuf, process};
+
+	use super::{Builtin, LambisError, Span, Value};
+
+	fn call(builtin: Builtin, args: &[&str]) -> Result<Value, LambisError> {
+		builtin.call(args.iter().map(|&arg: &&str| Value::String(arg.to_owned())).collect(), Span::default())
+	}
+
+	#[test]
+	fn files_are_written_appended_and_read() {
+		let file: PathBuf = env::temp_dir().join(format!("lambis-builtin-{}.txt", process::id()));
+		let path: &str = file.to_str().unwrap();
+
+		assert_eq!(call(Builtin::WriteFile, &[path, "one\n"]), Ok(Value::None));
+		assert_eq!(call(Builtin::AppendFile, &[path, "two\r\nthree"]), Ok(Value::None));
+		assert_eq!(call(Builtin::ReadFile, &[path]), Ok(Value::String("one\ntwo\r\nthree".to_owned())));
+		assert_eq!(call(Builtin::ReadLines, &[path]), Ok(Value::Array(vec![
+			Value::String("one".to_owned()),
+			Value::String("two".to_owned()),
+			Value::String("three".to_owned()),
+		])));
+
+		fs::remove_file(&file).unwrap();
+	}
+
+	#[test]
+	fn missing_files_are_runtime_errors() {
+		let missing: PathBuf = env::temp_dir().join(format!("lambis-missing-{}", process::id())).join("file.txt");
+		let path: &str = missing.to_str().unwrap();
+
+		for (builtin, args) in [
+			(Builtin::ReadFile, vec![path]),
+			(Builtin::ReadLines, vec![path]),
+			(Builtin::WriteFile, vec![path, "x"]),
+			(Builtin::AppendFile, vec![path, "x"]),
+		] {
+			let error: LambisError = call(builtin, &args).unwrap_err();
+			assert_eq!(error.kind(), "InterpreterError");
+			assert!(error.message().starts_with(&format!("{}: cannot access «{path}»: ", builtin.name())), "{}", error.message());
+		}
+	}
+
+	#[test]
+	fn file_builtins_only_take_strings() {
+		let error: LambisError = Builtin::ReadFile.call(vec![Value::Word("notes".to_owned())], Span::default()).unwrap_err();
+		assert_eq!(error.message(), "fileread: expected <String>, got Word(\"notes\")");
+
+		let error: LambisError = Builtin::WriteFile
+			.call(vec![Value::String("notes.txt".to_owned()), Value::Integer(1)], Span::default())
+			.unwrap_err();
+		assert_eq!(error.message(), "filewrite: expected <String>, got Integer(1)");
+	}
+}
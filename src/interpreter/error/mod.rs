@@ -0,0 +1,262 @@
+use std::{error, fmt};
+
+// byte offsets into the source, line and column are 1-based.
+// `file` is 0 for the evaluated input itself, imported modules get their own ids
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub struct Span {
+	pub start: usize,
+	pub end: usize,
+	pub line: usize,
+	pub column: usize,
+	pub file: usize,
+}
+
+impl Span {
+	pub fn to(self, other: Span) -> Span {
+		Span {
+			end: other.end.max(self.start),
+			..self
+		}
+	}
+}
+
+// the sandbox limits an evaluation can run out of
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Limit {
+	Steps,
+	Depth,
+	Nesting,
+	Values,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum LambisError {
+	Lex {
+		message: String,
+		span: Span,
+		hint: Option<String>
+	},
+
+	Parse {
+		message: String,
+		span: Span,
+		hint: Option<String>
+	},
+
+	Runtime {
+		message: String,
+		span: Span,
+		hint: Option<String>
+	},
+
+	Limit {
+		limit: Limit,
+		span: Span,
+		hint: Option<String>
+	},
+
+	Cancelled {
+		span: Span,
+		hint: Option<String>
+	},
+}
+
+impl LambisError {
+	pub fn lex(message: impl Into<String>, span: Span) -> Self {
+		LambisError::Lex {message: message.into(), span, hint: None}
+	}
+
+	pub fn parse(message: impl Into<String>, span: Span) -> Self {
+		LambisError::Parse {message: message.into(), span, hint: None}
+	}
+
+	pub fn runtime(message: impl Into<String>, span: Span) -> Self {
+		LambisError::Runtime {message: message.into(), span, hint: None}
+	}
+
+	pub fn limit(limit: Limit, span: Span) -> Self {
+		LambisError::Limit {limit, span, hint: None}
+	}
+
+	pub fn cancelled(span: Span) -> Self {
+		LambisError::Cancelled {span, hint: None}
+	}
+
+	pub fn with_hint(mut self, new_hint: impl Into<String>) -> Self {
+		match &mut self {
+			LambisError::Lex {hint, ..}
+			| LambisError::Parse {hint, ..}
+			| LambisError::Runtime {hint, ..}
+			| LambisError::Limit {hint, ..}
+			| LambisError::Cancelled {hint, ..} => *hint = Some(new_hint.into()),
+		}
+
+		self
+	}
+
+	pub fn kind(&self) -> &'static str {
+		match self {
+			LambisError::Lex {..} => "LexError",
+			LambisError::Parse {..} => "ParsingError",
+			LambisError::Runtime {..} => "InterpreterError",
+			LambisError::Limit {..} => "LimitError",
+			LambisError::Cancelled {..} => "Cancelled",
+		}
+	}
+
+	pub fn message(&self) -> &str {
+		match self {
+			LambisError::Lex {message, ..}
+			| LambisError::Parse {message, ..}
+			| LambisError::Runtime {message, ..} => message,
+			LambisError::Limit {limit: Limit::Steps, ..} => "ran out of evaluation steps",
+			LambisError::Limit {limit: Limit::Depth, ..} => "calls are nested too deep",
+			LambisError::Limit {limit: Limit::Nesting, ..} => "expressions are nested too deep",
+			LambisError::Limit {limit: Limit::Values, ..} => "allocated too many values",
+			LambisError::Cancelled {..} => "evaluation was cancelled",
+		}
+	}
+
+	pub fn span(&self) -> Span {
+		match self {
+			LambisError::Lex {span, ..}
+			| LambisError::Parse {span, ..}
+			| LambisError::Runtime {span, ..}
+			| LambisError::Limit {span, ..}
+			| LambisError::Cancelled {span, ..} => *span,
+		}
+	}
+
+	pub fn hint(&self) -> Option<&str> {
+		match self {
+			LambisError::Lex {hint, ..}
+			| LambisError::Parse {hint, ..}
+			| LambisError::Runtime {hint, ..}
+			| LambisError::Limit {hint, ..}
+			| LambisError::Cancelled {hint, ..} => hint.as_deref(),
+		}
+	}
+
+	// renders the error like a compiler diagnostic:
+	//
+	// error[InterpreterError]: variable «x» is undefined in current context
+	//  --> file.li:1:3
+	//   |
+	// 1 | ! x
+	//   |   ^
+	//   = help: did you mean `y`?
+	//
+	// errors that don't come from a place in the source, with a default span, only name the file
+	pub fn render(&self, file: &str, source: &str) -> String {
+		let span: Span = self.span();
+		if span.line == 0 {
+			let mut result: String = format!("error[{}]: {}\n --> {file}\n", self.kind(), self.message());
+			if let Some(hint) = self.hint() {
+				result += format!("  = help: {hint}\n").as_str();
+			}
+
+			return result;
+		}
+
+		let line: &str = source.lines().nth(span.line.saturating_sub(1)).unwrap_or("");
+		let number: String = span.line.to_string();
+		let gutter: String = " ".repeat(number.len());
+
+		let offset: usize = line.chars().take(span.column.saturating_sub(1)).map(char::len_utf8).sum();
+		let width: usize = source
+			.get(span.start..span.end)
+			.map_or(0, |s: &str| s.chars().take_while(|&ch: &char| ch != '\n').count())
+			.min(line[offset.min(line.len())..].chars().count())
+			.max(1);
+
+		let padding: String = line
+			.chars()
+			.take(span.column.saturating_sub(1))
+			.map(|ch: char| if ch == '\t' {'\t'} else {' '})
+			.collect();
+
+		let mut result: String = format!("error[{}]: {}\n", self.kind(), self.message());
+		result += format!("{gutter}--> {file}:{}:{}\n", span.line, span.column).as_str();
+		result += format!("{gutter} |\n").as_str();
+		result += format!("{number} | {line}\n").as_str();
+		result += format!("{gutter} | {padding}{}\n", "^".repeat(width)).as_str();
+
+		if let Some(hint) = self.hint() {
+			result += format!("{gutter} = help: {hint}\n").as_str();
+		}
+
+		result
+	}
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+	let b: Vec<char> = b.chars().collect();
+	let mut row: Vec<usize> = (0..=b.len()).collect();
+
+	for (i, ca) in a.chars().enumerate() {
+		let mut previous: usize = row[0];
+		row[0] = i + 1;
+
+		for (j, &cb) in b.iter().enumerate() {
+			let current: usize = row[j + 1];
+			row[j + 1] = if ca == cb {
+				previous
+			} else {
+				1 + previous.min(row[j]).min(current)
+			};
+			previous = current;
+		}
+	}
+
+	row[b.len()]
+}
+
+// finds the candidate closest to `name`, if any is close enough to be a likely typo
+pub fn closest<'a>(name: &str, candidates: impl Iterator<Item=&'a str>) -> Option<&'a str> {
+	let limit: usize = (name.chars().count() / 3).max(1);
+
+	candidates
+		.filter(|&candidate: &&str| candidate != name)
+		.map(|candidate: &str| (edit_distance(name, candidate), candidate))
+		.filter(|&(distance, _): &(usize, &str)| distance <= limit)
+		.min()
+		.map(|(_, candidate): (usize, &str)| candidate)
+}
+
+impl fmt::Display for LambisError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		let span: Span = self.span();
+		write!(f, "{} at {}:{}: {}", self.kind(), span.line, span.column, self.message())
+	}
+}
+
+impl error::Error for LambisError {}
+
+#[cfg(test)]
+mod tests {
+	use super::{LambisError, Span};
+
+	#[test]
+	fn render_points_at_the_span() {
+		let span: Span = Span {start: 2, end: 3, line: 1, column: 3, file: 0};
+		let error: LambisError = LambisError::runtime("variable «x» is undefined in current context", span).with_hint("did you mean `y`?");
+		assert_eq!(error.render("file.li", "! x"), concat!(
+			"error[InterpreterError]: variable «x» is undefined in current context\n",
+			" --> file.li:1:3\n",
+			"  |\n",
+			"1 | ! x\n",
+			"  |   ^\n",
+			"  = help: did you mean `y`?\n",
+		));
+	}
+
+	#[test]
+	fn render_skips_the_source_for_a_default_span() {
+		let error: LambisError = LambisError::runtime("«f» holds a function the vm can't call", Span::default()).with_hint("set it on a vm");
+		assert_eq!(error.render("file.li", "! x"), concat!(
+			"error[InterpreterError]: «f» holds a function the vm can't call\n",
+			" --> file.li\n",
+			"  = help: set it on a vm\n",
+		));
+	}
+}
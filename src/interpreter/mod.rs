@@ -1,209 +1,999 @@
+pub mod bigint;
+pub mod builtin;
+pub mod error;
+pub mod module;
 pub mod parser;
-use std::collections::HashMap;
-
-fn integer_operation(op: &parser::Operation, left: i128, right: i128) -> parser::Value {
-	match op {
-		parser::Operation::Addition => parser::Value::Integer(left + right),
-		parser::Operation::Substraction => parser::Value::Integer(left - right),
-		parser::Operation::Multiplication => parser::Value::Integer(left * right),
-		parser::Operation::Division => {
-			if left % right == 0 {
-				parser::Value::Integer(left / right)
+pub mod rational;
+pub mod vm;
+pub mod walker;
+use std::{
+	collections::{HashMap, HashSet},
+	io::{self, BufRead},
+	path::{Path, PathBuf},
+	sync::{atomic::{AtomicBool, Ordering}, Arc},
+	time::{Duration, Instant},
+};
+use bigint::BigInt;
+use error::{LambisError, Limit, Span};
+use parser::pattern::Pattern;
+use rational::Rational;
+
+// how programs are executed: walking the syntax tree, which is the reference
+// implementation, or compiling it to bytecode for the stack machine in `vm`
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum Backend {
+	#[default]
+	TreeWalker,
+	Vm,
+}
+
+impl Backend {
+	// lambdas are syntax trees only the tree-walker runs and closures are bytecode only
+	// the vm runs, so a function made by one backend can't be called by the other
+	pub(crate) fn can_call(self, value: &parser::Value) -> bool {
+		match value {
+			parser::Value::Lambda {..} => self == Backend::TreeWalker,
+			parser::Value::Closure(..) => self == Backend::Vm,
+			parser::Value::Array(items)
+			| parser::Value::Builtin {applied: items, ..}
+			| parser::Value::Native {applied: items, ..} => items.iter().all(|item: &parser::Value| self.can_call(item)),
+			_ => true,
+		}
+	}
+}
+
+// bounds for running untrusted scripts, each evaluation starts with all of them
+// available again. `None` leaves a limit out
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub struct Limits {
+	// function applications, which is what every loop is made of
+	pub steps: Option<u64>,
+	// calls waiting for the result of another one, tail calls don't nest
+	pub depth: Option<usize>,
+	// how deep expressions can be nested in the source, every operation of a chain nesting the rest
+	// of it. Parsing and compiling recurse for each level, so deeper input is refused by the parser
+	pub nesting: Option<usize>,
+	// items of the arrays and characters of the strings the script builds
+	pub values: Option<u64>,
+	// refuses printing, reading input, importing and the file builtins,
+	// and bounds the depth and the nesting when they are left out
+	pub sandbox: bool,
+	// wall-clock time after which the evaluation is cancelled
+	pub timeout: Option<Duration>,
+}
+
+// few calls waiting on the heap, and nesting that parses within
+// the 2 MiB stack of a spawned thread even in a debug build
+const SANDBOX_DEPTH: usize = 1_000;
+const SANDBOX_NESTING: usize = 200;
+// the clock is only read every this many steps, reading it on every call slows them all down
+const CLOCK_EVERY: u64 = 1_024;
+
+impl Limits {
+	fn depth(&self) -> Option<usize> {
+		self.depth.or(self.sandbox.then_some(SANDBOX_DEPTH))
+	}
+
+	fn nesting(&self) -> Option<usize> {
+		self.nesting.or(self.sandbox.then_some(SANDBOX_NESTING))
+	}
+}
+
+// what the running evaluation has used of its limits
+#[derive(Default)]
+struct Usage {
+	steps: u64,
+	depth: usize,
+	values: u64,
+	deadline: Option<Instant>,
+}
+
+// lets another thread stop the running evaluation at its next function application.
+// A request made while none is running is dropped when the next one starts. Input and files are waited for
+#[derive(Debug, Clone, Default)]
+pub struct Cancellation(Arc<AtomicBool>);
+
+impl Cancellation {
+	pub fn cancel(&self) {
+		self.0.store(true, Ordering::Relaxed);
+	}
+
+	fn reset(&self) {
+		self.0.store(false, Ordering::Relaxed);
+	}
+
+	fn cancelled(&self) -> bool {
+		self.0.load(Ordering::Relaxed)
+	}
+}
+
+pub struct Environment {
+	pub variables: HashMap<String, parser::Value>,
+	// the globals scripts defined, which are constants unless `redefine` is set,
+	// unlike builtins and what the embedding program sets, which they can shadow
+	defined: HashSet<String>,
+	pub redefine: bool,
+	pub modules: module::Modules,
+	pub backend: Backend,
+	pub limits: Limits,
+	pub cancellation: Cancellation,
+	usage: Usage,
+}
+
+fn within<T: PartialOrd + std::fmt::Display>(used: T, limit: Option<T>, which: Limit, span: Span) -> Result<(), LambisError> {
+	match limit {
+		Some(limit) if used > limit => Err(LambisError::limit(which, span).with_hint(format!("the limit is {limit}"))),
+		_ => Ok(()),
+	}
+}
+
+// integers only stay big while they don't fit in an `i128`
+fn integer(number: BigInt) -> parser::Value {
+	match number.to_i128() {
+		Some(small) => parser::Value::Integer(small),
+		None => parser::Value::BigInt(number),
+	}
+}
+
+// and rationals are only kept while they aren't integers
+fn rational(number: Rational) -> parser::Value {
+	if number.is_integer() {
+		integer(number.numerator().clone())
+	} else {
+		parser::Value::Rational(number)
+	}
+}
+
+// exact powers bigger than this many digits are refused instead of exhausting the memory
+const MAX_POWER_DIGITS: f64 = 100_000.0;
+
+fn power(base: &Rational, exponent: &BigInt) -> Result<parser::Value, String> {
+	let out_of_range = || format!("exponent {exponent} is out of range");
+	let magnitude: u32 = exponent
+		.abs()
+		.to_i128()
+		.and_then(|magnitude: i128| u32::try_from(magnitude).ok())
+		.ok_or_else(out_of_range)?;
+
+	let digits: f64 = base.numerator().log10().max(base.denominator().log10()) * f64::from(magnitude);
+	if digits > MAX_POWER_DIGITS {
+		return Err(format!("{}, the result would have about {digits:.0} digits", out_of_range()));
+	}
+
+	let result: Rational = base.pow(magnitude);
+	if exponent.is_negative() {
+		Ok(rational(Rational::from(BigInt::from(1)).checked_div(&result).ok_or("division by zero")?))
+	} else {
+		Ok(rational(result))
+	}
+}
+
+fn integer_operation(op: &parser::Operation, left: i128, right: i128) -> Result<parser::Value, String> {
+	// overflowing operations are redone with big integers
+	let exact = |result: Option<i128>| -> Result<parser::Value, String> {
+		match result {
+			Some(result) => Ok(parser::Value::Integer(result)),
+			None => bigint_operation(op, &BigInt::from(left), &BigInt::from(right)),
+		}
+	};
+
+	Ok(match op {
+		parser::Operation::Addition => return exact(left.checked_add(right)),
+		parser::Operation::Substraction => return exact(left.checked_sub(right)),
+		parser::Operation::Multiplication => return exact(left.checked_mul(right)),
+		parser::Operation::Division => match left.checked_rem(right) {
+			Some(0) => return exact(left.checked_div(right)),
+			// inexact, by zero or overflowing
+			_ => return bigint_operation(op, &BigInt::from(left), &BigInt::from(right)),
+		}
+
+		parser::Operation::FloorDivision => return exact(left
+			.checked_div(right)
+			.zip(left.checked_rem(right))
+			.map(|(quotient, remainder): (i128, i128)| {
+				if remainder != 0 && (remainder < 0) != (right < 0) { quotient - 1 } else { quotient }
+			})),
+
+		parser::Operation::Remainder => return exact(left
+			.checked_rem(right)
+			.map(|remainder: i128| {
+				if remainder != 0 && (remainder < 0) != (right < 0) { remainder + right } else { remainder }
+			})),
+
+		parser::Operation::Exponent => match u32::try_from(right) {
+			Ok(exponent) => return exact(left.checked_pow(exponent)),
+			Err(_) => return power(&Rational::from(BigInt::from(left)), &BigInt::from(right)),
+		}
+
+		parser::Operation::Less => parser::Value::Bool(left < right),
+		parser::Operation::LessEqual => parser::Value::Bool(left <= right),
+		parser::Operation::Greater => parser::Value::Bool(left > right),
+		parser::Operation::GreaterEqual => parser::Value::Bool(left >= right),
+		parser::Operation::Equal => parser::Value::Bool(left == right),
+		parser::Operation::NotEqual => parser::Value::Bool(left != right),
+		_ => return Err(format!("unsupported operation {op:?} for integers")),
+	})
+}
+
+fn bigint_operation(op: &parser::Operation, left: &BigInt, right: &BigInt) -> Result<parser::Value, String> {
+	Ok(match op {
+		parser::Operation::Addition => integer(left + right),
+		parser::Operation::Substraction => integer(left - right),
+		parser::Operation::Multiplication => integer(left * right),
+		parser::Operation::Division => rational(Rational::new(left.clone(), right.clone()).ok_or("division by zero")?),
+		parser::Operation::FloorDivision | parser::Operation::Remainder => {
+			let quotient: BigInt = Rational::new(left.clone(), right.clone()).ok_or("division by zero")?.floor();
+			if *op == parser::Operation::FloorDivision {
+				integer(quotient)
 			} else {
-				parser::Value::Decimal((left as f64) / (right as f64))
+				integer(left - &(right * &quotient))
 			}
 		}
 
-		parser::Operation::Exponent => parser::Value::Decimal((left as f64).powi(i32::try_from(right).unwrap())),
+		parser::Operation::Exponent => return power(&Rational::from(left.clone()), right),
 
-		parser::Operation::Less => parser::Value::Integer(i128::from(left < right)),
-		parser::Operation::LessEqual => parser::Value::Integer(i128::from(left <= right)),
-		parser::Operation::Greater => parser::Value::Integer(i128::from(left > right)),
-		parser::Operation::GreaterEqual => parser::Value::Integer(i128::from(left >= right)),
-		parser::Operation::Equal => parser::Value::Integer(i128::from(left == right)),
-		parser::Operation::NotEqual => parser::Value::Integer(i128::from(left != right)),
-		//_ => panic!("InterpretationError: Unsupported operation for integers"),
-	}
+		parser::Operation::Less => parser::Value::Bool(left < right),
+		parser::Operation::LessEqual => parser::Value::Bool(left <= right),
+		parser::Operation::Greater => parser::Value::Bool(left > right),
+		parser::Operation::GreaterEqual => parser::Value::Bool(left >= right),
+		parser::Operation::Equal => parser::Value::Bool(left == right),
+		parser::Operation::NotEqual => parser::Value::Bool(left != right),
+		_ => return Err(format!("unsupported operation {op:?} for integers")),
+	})
+}
+
+fn rational_operation(op: &parser::Operation, left: &Rational, right: &Rational) -> Result<parser::Value, String> {
+	Ok(match op {
+		parser::Operation::Addition => rational(left + right),
+		parser::Operation::Substraction => rational(left - right),
+		parser::Operation::Multiplication => rational(left * right),
+		parser::Operation::Division => rational(left.checked_div(right).ok_or("division by zero")?),
+		parser::Operation::FloorDivision | parser::Operation::Remainder => {
+			let quotient: BigInt = left.checked_div(right).ok_or("division by zero")?.floor();
+			if *op == parser::Operation::FloorDivision {
+				integer(quotient)
+			} else {
+				rational(left - &(right * &Rational::from(quotient)))
+			}
+		}
+		parser::Operation::Exponent if right.is_integer() => return power(left, right.numerator()),
+		parser::Operation::Exponent => parser::Value::Decimal(left.to_f64().powf(right.to_f64())),
+
+		parser::Operation::Less => parser::Value::Bool(left < right),
+		parser::Operation::LessEqual => parser::Value::Bool(left <= right),
+		parser::Operation::Greater => parser::Value::Bool(left > right),
+		parser::Operation::GreaterEqual => parser::Value::Bool(left >= right),
+		parser::Operation::Equal => parser::Value::Bool(left == right),
+		parser::Operation::NotEqual => parser::Value::Bool(left != right),
+		_ => return Err(format!("unsupported operation {op:?} for rationals")),
+	})
+}
+
+fn decimal_operation(op: &parser::Operation, left: f64, right: f64) -> Result<parser::Value, String> {
+	Ok(match op {
+		parser::Operation::Addition => parser::Value::Decimal(left + right),
+		parser::Operation::Substraction => parser::Value::Decimal(left - right),
+		parser::Operation::Multiplication => parser::Value::Decimal(left * right),
+		parser::Operation::Division => parser::Value::Decimal(left / right),
+		parser::Operation::FloorDivision => parser::Value::Decimal((left / right).floor()),
+		parser::Operation::Remainder => parser::Value::Decimal(left - right * (left / right).floor()),
+		parser::Operation::Exponent => parser::Value::Decimal(left.powf(right)),
+
+		parser::Operation::Less => parser::Value::Bool(left < right),
+		parser::Operation::LessEqual => parser::Value::Bool(left <= right),
+		parser::Operation::Greater => parser::Value::Bool(left > right),
+		parser::Operation::GreaterEqual => parser::Value::Bool(left >= right),
+		parser::Operation::Equal => parser::Value::Bool((left - right).abs() < f64::EPSILON),
+		parser::Operation::NotEqual => parser::Value::Bool((left - right).abs() > f64::EPSILON),
+		_ => return Err(format!("unsupported operation {op:?} for decimals")),
+	})
 }
 
-fn decimal_operation(op: &parser::Operation, left: f64, right: f64) -> parser::Value {
-	parser::Value::Decimal(match op {
-		parser::Operation::Addition => left + right,
-		parser::Operation::Substraction => left - right,
-		parser::Operation::Multiplication => left * right,
-		parser::Operation::Division => left / right,
-		parser::Operation::Exponent => left.powf(right),
+fn string_operation(op: &parser::Operation, left: &str, right: &str) -> Result<parser::Value, String> {
+	Ok(match op {
+		parser::Operation::Addition | parser::Operation::Concat => parser::Value::String(left.to_owned() + right),
+		parser::Operation::Index => return Err("string index must be an integer".to_owned()),
 
-		parser::Operation::Less => f64::from(left < right),
-		parser::Operation::LessEqual => f64::from(left <= right),
-		parser::Operation::Greater => f64::from(left > right),
-		parser::Operation::GreaterEqual => f64::from(left >= right),
-		parser::Operation::Equal => f64::from((left - right).abs() < f64::EPSILON),
-		parser::Operation::NotEqual => f64::from((left - right).abs() > f64::EPSILON),
-		//_ => panic!("InterpretationError: Unsupported operation for decimals"),
+		parser::Operation::Less => parser::Value::Bool(left < right),
+		parser::Operation::LessEqual => parser::Value::Bool(left <= right),
+		parser::Operation::Greater => parser::Value::Bool(left > right),
+		parser::Operation::GreaterEqual => parser::Value::Bool(left >= right),
+		parser::Operation::Equal => parser::Value::Bool(left == right),
+		parser::Operation::NotEqual => parser::Value::Bool(left != right),
+		_ => return Err(format!("unsupported operation {op:?} for strings")),
 	})
 }
 
-fn operation(op: &parser::Operation, left: parser::Value, right: parser::Value) -> parser::Value {
-	match (left, right) {
+// how many values the result of building an array or a string takes
+fn size(value: &parser::Value) -> u64 {
+	match value {
+		parser::Value::Array(items) => items.len() as u64,
+		parser::Value::String(s) | parser::Value::Word(s) => s.chars().count() as u64,
+		_ => 0,
+	}
+}
+
+fn index<T>(mut items: impl Iterator<Item=T>, index: i128) -> Result<T, String> {
+	usize::try_from(index)
+		.ok()
+		.and_then(|i: usize| items.nth(i))
+		.ok_or_else(|| format!("index {index} is out of range"))
+}
+
+// booleans count as 1 and 0 in arithmetic and comparisons, the integers they were before they had their own type
+fn numeric(op: &parser::Operation, value: parser::Value) -> parser::Value {
+	match value {
+		parser::Value::Bool(bool) if !matches!(op,
+			parser::Operation::Concat | parser::Operation::Index | parser::Operation::Cons | parser::Operation::And | parser::Operation::Or
+		) => parser::Value::Integer(i128::from(bool)),
+		value => value,
+	}
+}
+
+fn operation(op: &parser::Operation, left: parser::Value, right: parser::Value, span: Span) -> Result<parser::Value, LambisError> {
+	let result: Result<parser::Value, String> = match (numeric(op, left), numeric(op, right)) {
 		(parser::Value::Integer(n1), parser::Value::Integer(n2)) => {
 			integer_operation(op, n1, n2)
 		}
+		(parser::Value::Integer(n1), parser::Value::BigInt(n2)) => {
+			bigint_operation(op, &BigInt::from(n1), &n2)
+		}
+		(parser::Value::BigInt(n1), parser::Value::Integer(n2)) => {
+			bigint_operation(op, &n1, &BigInt::from(n2))
+		}
+		(parser::Value::BigInt(n1), parser::Value::BigInt(n2)) => {
+			bigint_operation(op, &n1, &n2)
+		}
+		(parser::Value::Rational(n1), parser::Value::Rational(n2)) => {
+			rational_operation(op, &n1, &n2)
+		}
+		(parser::Value::Rational(n1), parser::Value::Integer(n2)) => {
+			rational_operation(op, &n1, &Rational::from(BigInt::from(n2)))
+		}
+		(parser::Value::Integer(n1), parser::Value::Rational(n2)) => {
+			rational_operation(op, &Rational::from(BigInt::from(n1)), &n2)
+		}
+		(parser::Value::Rational(n1), parser::Value::BigInt(n2)) => {
+			rational_operation(op, &n1, &Rational::from(n2))
+		}
+		(parser::Value::BigInt(n1), parser::Value::Rational(n2)) => {
+			rational_operation(op, &Rational::from(n1), &n2)
+		}
 		(parser::Value::Integer(n1), parser::Value::Decimal(n2)) => {
 			decimal_operation(op, n1 as f64, n2)
 		}
+		(parser::Value::Rational(n1), parser::Value::Decimal(n2)) => {
+			decimal_operation(op, n1.to_f64(), n2)
+		}
+		(parser::Value::Decimal(n1), parser::Value::Rational(n2)) => {
+			decimal_operation(op, n1, n2.to_f64())
+		}
+		(parser::Value::BigInt(n1), parser::Value::Decimal(n2)) => {
+			decimal_operation(op, n1.to_f64(), n2)
+		}
+		(parser::Value::Decimal(n1), parser::Value::BigInt(n2)) => {
+			decimal_operation(op, n1, n2.to_f64())
+		}
 		(parser::Value::Decimal(n1), parser::Value::Integer(n2)) => {
 			decimal_operation(op, n1, n2 as f64)
 		}
 		(parser::Value::Decimal(n1), parser::Value::Decimal(n2)) => {
 			decimal_operation(op, n1, n2)
 		}
-		_ => panic!("InterpretationError: Unsupported value types"),
+		(parser::Value::String(s1), parser::Value::String(s2)) => {
+			string_operation(op, s1.as_str(), s2.as_str())
+		}
+		(item, parser::Value::Array(mut items)) if *op == parser::Operation::Cons => {
+			items.insert(0, item);
+			Ok(parser::Value::Array(items))
+		}
+		(parser::Value::Array(mut a1), parser::Value::Array(a2)) if *op == parser::Operation::Concat => {
+			a1.extend(a2);
+			Ok(parser::Value::Array(a1))
+		}
+		(parser::Value::Word(w1), parser::Value::Word(w2)) if *op == parser::Operation::Concat => {
+			Ok(parser::Value::Word(w1 + w2.as_str()))
+		}
+		(parser::Value::Array(items), parser::Value::Integer(n)) if *op == parser::Operation::Index => {
+			index(items.into_iter(), n)
+		}
+		(parser::Value::String(s), parser::Value::Integer(n)) if *op == parser::Operation::Index => {
+			index(s.chars(), n).map(|ch: char| parser::Value::String(ch.to_string()))
+		}
+		(parser::Value::Word(s), parser::Value::Integer(n)) if *op == parser::Operation::Index => {
+			index(s.chars(), n).map(|ch: char| parser::Value::Word(ch.to_string()))
+		}
+		(parser::Value::Array(_) | parser::Value::String(_) | parser::Value::Word(_), parser::Value::BigInt(n)) if *op == parser::Operation::Index => {
+			Err(format!("index {n} is out of range"))
+		}
+		(left, right) if *op == parser::Operation::Equal => {
+			Ok(parser::Value::Bool(matches_case(&left, &right)))
+		}
+		(left, right) if *op == parser::Operation::NotEqual => {
+			Ok(parser::Value::Bool(!matches_case(&left, &right)))
+		}
+		(left, right) => Err(format!("unsupported value types for {op:?}: {left} and {right}")),
+	};
+
+	result.map_err(|message: String| LambisError::runtime(message, span))
+}
+
+// `operation`, charging the arrays and strings it builds to the values limit
+fn build(op: &parser::Operation, left: parser::Value, right: parser::Value, span: Span, env: &mut Environment) -> Result<parser::Value, LambisError> {
+	let result: parser::Value = operation(op, left, right, span)?;
+	if matches!(op, parser::Operation::Addition | parser::Operation::Concat | parser::Operation::Cons) {
+		env.allocate(size(&result), span)?;
 	}
+
+	Ok(result)
 }
 
-pub fn eval(node: parser::ASTNode, variables: &mut HashMap<String, parser::Value>, args: &mut HashMap<char, parser::Value>) -> parser::Value {
-	match node {
-		parser::ASTNode::Nothing => parser::Value::None,
+// conditions are never converted to booleans
+fn condition(name: &str, value: parser::Value, span: Span) -> Result<bool, LambisError> {
+	match value {
+		parser::Value::Bool(bool) => Ok(bool),
+		what => Err(LambisError::runtime(format!("{name}: expected <Bool>, got {what:?}"), span)),
+	}
+}
 
-		parser::ASTNode::Print(value_) => {
-			let value: parser::Value = eval(*value_, variables, args);
-			match value {
-				parser::Value::Lambda {args_def, ..} => println!("<λ{args_def}.>"),
-				parser::Value::Integer(int) => println!("{int}"),
-				parser::Value::Decimal(dec) => println!("{dec}"),
-                parser::Value::Word(s) => println!("'{s}"),
-				parser::Value::None => println!("Nothing"),
-				parser::Value::Variable(_) => unreachable!("how tf you achieved variable after eval"),
-			}
+fn truth(op: &parser::Operation, value: parser::Value, span: Span) -> Result<bool, LambisError> {
+	condition(if *op == parser::Operation::And { "&&" } else { "||" }, value, span)
+}
 
-			parser::Value::None
+// collects what the bindings of `pattern` stand for in `value`, in the order of `Pattern::names`.
+// `pins` are what the pinned expressions evaluated to, in the order of `Pattern::pins`
+fn matches(pattern: &Pattern, value: &parser::Value, pins: &mut impl Iterator<Item = parser::Value>, bound: &mut Vec<parser::Value>) -> bool {
+	match (pattern, value) {
+		(Pattern::Wildcard, _) => true,
+		(Pattern::Binding(_), value) => {
+			bound.push(value.clone());
+			true
 		}
 
-		parser::ASTNode::RationalPart(value_) => {
-			let value: parser::Value = eval(*value_, variables, args);
-			match value {
-				parser::Value::Integer(_) => parser::Value::Integer(0),
-				parser::Value::Decimal(n) =>parser::Value::Decimal(n-n.floor()),
-				what => panic!("InterpreterError: {{_}}: expected <Integer|Decimal>, got {what:?}"),
-			}
+		(Pattern::Literal(literal), value) => equal(value, literal),
+		(Pattern::Pinned(_), value) => pins.next().is_some_and(|pin: parser::Value| equal(value, &pin)),
+		(Pattern::Range(from, to), value) => {
+			let holds = |op: parser::Operation, left: &parser::Value, right: &parser::Value| -> bool {
+				operation(&op, left.clone(), right.clone(), Span::default()) == Ok(parser::Value::Bool(true))
+			};
+
+			holds(parser::Operation::GreaterEqual, value, from) && holds(parser::Operation::LessEqual, value, to)
 		}
 
-		parser::ASTNode::IntegerPart(value_) => {
-			let value: parser::Value = eval(*value_, variables, args);
-			match value {
-				parser::Value::Integer(_) => value,
-				parser::Value::Decimal(n) => parser::Value::Integer(n.floor() as i128),
-				what => panic!("InterpreterError: [_]: Expected <Integer|Decimal>, got {what:?}"),
-			}
+		(Pattern::Cons(head, tail), parser::Value::Array(items)) => items
+			.split_first()
+			.is_some_and(|(first, rest): (&parser::Value, &[parser::Value])| {
+				matches(head, first, pins, bound) && matches(tail, &parser::Value::Array(rest.to_vec()), pins, bound)
+			}),
+
+		(Pattern::Array(patterns), parser::Value::Array(items)) => {
+			patterns.len() == items.len()
+				&& patterns.iter().zip(items).all(|(pattern, item): (&Pattern, &parser::Value)| matches(pattern, item, pins, bound))
 		}
 
-		parser::ASTNode::Switch {compared: compared_, cases} => {
-			let compared: parser::Value = eval(*compared_, variables, args);
-			for (case_, action) in cases {
-				let case: parser::Value = eval(case_, variables, args);
-				if compared == case {
-					return eval(action, variables, args);
-				}
+		_ => false,
+	}
+}
+
+// `=`, so numbers match whatever they are represented as
+fn equal(left: &parser::Value, right: &parser::Value) -> bool {
+	operation(&parser::Operation::Equal, left.clone(), right.clone(), Span::default()) == Ok(parser::Value::Bool(true))
+}
+
+fn matches_case(compared: &parser::Value, case: &parser::Value) -> bool {
+	match (compared, case) {
+		(parser::Value::Array(items), parser::Value::None)
+		| (parser::Value::None, parser::Value::Array(items)) => items.is_empty(),
+		_ => compared == case,
+	}
+}
+
+fn read_input(numeric: bool, span: Span) -> Result<parser::Value, LambisError> {
+	read_line(&mut io::stdin().lock(), numeric, span)
+}
+
+// `?` and `??` over a line of `input`, Nothing once it has run out
+fn read_line(input: &mut impl BufRead, numeric: bool, span: Span) -> Result<parser::Value, LambisError> {
+	let mut line: String = String::new();
+	let read: usize = input
+		.read_line(&mut line)
+		.map_err(|error: io::Error| LambisError::runtime(format!("cannot read input: {error}"), span))?;
+
+	if read == 0 {
+		return Ok(parser::Value::None);
+	}
+
+	let line: &str = line.trim_end_matches(['\n', '\r']);
+	if !numeric {
+		return Ok(parser::Value::String(line.to_owned()));
+	}
+
+	let trimmed: &str = line.trim();
+	if let Some(number) = BigInt::parse(trimmed) {
+		Ok(integer(number))
+	} else if let Ok(decimal) = trimmed.parse::<f64>() {
+		Ok(parser::Value::Decimal(decimal))
+	} else {
+		Err(LambisError::runtime(format!("expected a number as input, got «{line}»"), span))
+	}
+}
+
+fn length(value: parser::Value, span: Span) -> Result<parser::Value, LambisError> {
+	Ok(match value {
+		parser::Value::Array(items) => parser::Value::Integer(items.len() as i128),
+		parser::Value::String(s) | parser::Value::Word(s) => parser::Value::Integer(s.chars().count() as i128),
+		parser::Value::None => parser::Value::Integer(0),
+		what => return Err(LambisError::runtime(format!("|_|: expected <Array|String|Word>, got {what:?}"), span)),
+	})
+}
+
+fn rational_part(value: parser::Value, span: Span) -> Result<parser::Value, LambisError> {
+	Ok(match value {
+		parser::Value::Integer(_) | parser::Value::BigInt(_) | parser::Value::Bool(_) => parser::Value::Integer(0),
+		parser::Value::Rational(n) => rational(&n - &Rational::from(n.floor())),
+		parser::Value::Decimal(n) =>parser::Value::Decimal(n-n.floor()),
+		what => return Err(LambisError::runtime(format!("{{_}}: expected <Integer|Rational|Decimal>, got {what:?}"), span)),
+	})
+}
+
+fn integer_part(value: parser::Value, span: Span) -> Result<parser::Value, LambisError> {
+	Ok(match value {
+		parser::Value::Integer(_) | parser::Value::BigInt(_) => value,
+		parser::Value::Bool(bool) => parser::Value::Integer(i128::from(bool)),
+		parser::Value::Rational(n) => integer(n.floor()),
+		parser::Value::Decimal(n) => match BigInt::from_f64(n.floor()) {
+			Some(floor) => integer(floor),
+			None => return Err(LambisError::runtime(format!("cannot take the integer part of {n}"), span)),
+		},
+		what => return Err(LambisError::runtime(format!("[_]: Expected <Integer|Rational|Decimal>, got {what:?}"), span)),
+	})
+}
+
+fn undefined_variable<'a>(name: &str, candidates: impl Iterator<Item=&'a str>, span: Span) -> LambisError {
+	let error: LambisError = LambisError::runtime(format!("variable «{name}» is undefined in current context"), span);
+
+	match error::closest(name, candidates) {
+		Some(similar) => error.with_hint(format!("did you mean `{similar}`?")),
+		None => error,
+	}
+}
+
+// calls a builtin or a native once every argument is given, otherwise keeps the applied ones for later
+fn apply_function(mut function: parser::Value, args: Vec<parser::Value>, span: Span, env: &Environment) -> Result<parser::Value, LambisError> {
+	let (name, arity, applied): (&str, usize, &mut Vec<parser::Value>) = match &mut function {
+		parser::Value::Builtin {builtin, applied} => {
+			if builtin.touches_files() {
+				env.permit(format!("«{}»", builtin.name()).as_str(), span)?;
 			}
 
-			parser::Value::None
+			(builtin.name(), builtin.arity(), applied)
 		}
 
-		parser::ASTNode::Definition {name, value} => {
-			let res: parser::Value = eval(*value, variables, args);
-			variables.insert(name, res.clone());
-			res
+		parser::Value::Native {native, applied} => (native.name.as_str(), native.arity, applied),
+		_ => unreachable!("only builtins and natives are applied to their arguments"),
+	};
+
+	applied.extend(args);
+	let len: usize = applied.len();
+	if len > arity {
+		return Err(LambisError::runtime(format!("too much arguments ({len}/{arity}) for «{name}»"), span));
+	}
+
+	Ok(match function {
+		_ if len < arity => function,
+		parser::Value::Builtin {builtin, applied} => builtin.call(applied, span)?,
+		parser::Value::Native {native, applied} => native.call(applied, span)?,
+		_ => unreachable!("only builtins and natives are applied to their arguments"),
+	})
+}
+
+pub fn default_variables() -> HashMap<String, parser::Value> {
+	let mut variables: HashMap<String, parser::Value> = HashMap::new();
+
+	variables.insert("true".to_owned(), parser::Value::Bool(true));
+	variables.insert("false".to_owned(), parser::Value::Bool(false));
+
+	for builtin in builtin::Builtin::ALL {
+		variables.insert(builtin.name().to_owned(), parser::Value::Builtin {
+			builtin,
+			applied: vec![],
+		});
+	}
+
+	variables
+}
+
+fn load_module(path: &Path, namespace: &str, span: Span, env: &mut Environment) -> Result<Vec<String>, LambisError> {
+	let (file, source): (usize, String) = env.modules.read(path, span)?;
+	let nodes: Vec<parser::ASTNode> = parser::parse_file(source.as_str(), file, env.limits.nesting())?;
+	let names: Vec<String> = module::definitions(&nodes);
+	let own: HashSet<String> = names.iter().cloned().collect();
+
+	env.execute(nodes
+		.into_iter()
+		.map(|node: parser::ASTNode| module::qualify(node, &own, namespace))
+		.collect())?;
+
+	Ok(names)
+}
+
+fn import(path: &str, namespace: Option<String>, span: Span, env: &mut Environment) -> Result<(), LambisError> {
+	let resolved: PathBuf = env.modules.resolve(path, span)?;
+	let namespace: String = namespace.unwrap_or_else(|| module::default_namespace(&resolved));
+
+	if let Some(loaded) = env.modules.get(&resolved) {
+		if loaded.namespace == namespace {
+			return Ok(());
 		}
 
-		parser::ASTNode::Value(val) => {
-			match val {
-				parser::Value::Variable(name) => {
-					let first: char = name.chars().next().unwrap();
+		// already evaluated under another namespace, so only alias its definitions
+		let aliases: Vec<(String, parser::Value)> = loaded.names
+			.iter()
+			.filter_map(|name: &String| {
+				env.variables
+					.get(format!("{}::{name}", loaded.namespace).as_str())
+					.map(|value: &parser::Value| (format!("{namespace}::{name}"), value.clone()))
+			}).collect();
 
-					if variables.contains_key(&name) {
-						variables[&name].clone()
-					} else if name.len() == 1 && args.contains_key(&first) {
-						args[&first].clone()
-					} else {
-						panic!("InterpreterError: variable «{name}» is undefined in current context")
-					}
-				}
+		env.variables.extend(aliases);
+		return Ok(());
+	}
 
-				_ => val,
-			}
+	env.modules.enter(&resolved, span)?;
+	let result: Result<Vec<String>, LambisError> = load_module(&resolved, namespace.as_str(), span, env);
+	env.modules.leave();
+
+	env.modules.finish(resolved, module::Module {
+		namespace,
+		names: result?,
+	});
+	Ok(())
+}
+
+impl Environment {
+	// `root` is the directory imports of the evaluated input are resolved from
+	pub fn new(root: PathBuf) -> Self {
+		Self {
+			variables: default_variables(),
+			defined: HashSet::new(),
+			redefine: false,
+			modules: module::Modules::new(root),
+			backend: Backend::default(),
+			limits: Limits::default(),
+			cancellation: Cancellation::default(),
+			usage: Usage::default(),
 		}
+	}
 
-		parser::ASTNode::LambdaCall {lambda, args: args_} => {
-			let parser::Value::Lambda {args_def, content} = *lambda.clone() else {
-				unreachable!("how the fuck you managed to get non-lambda in LambdaCall???")
-			};
+	// runs top-level statements with the selected backend, returning the value of the last one
+	pub fn run(&mut self, nodes: Vec<parser::ASTNode>) -> Result<parser::Value, LambisError> {
+		self.usage = Usage {
+			deadline: self.limits.timeout.and_then(|timeout: Duration| Instant::now().checked_add(timeout)),
+			..Usage::default()
+		};
+		self.cancellation.reset();
 
-			let mut new_args: HashMap<char, parser::Value> = args.clone();
-			let len: usize = args_.len();
-			let len2: usize = args_def.len();
-			assert!(len <= len2, "InterpreterError: too much arguments ({len}/{len2}) for «{lambda:?}»");
-
-			if len < len2 {
-				parser::Value::Lambda {
-					args_def: args_def[len..].to_owned(),
-					content: Box::new(parser::ASTNode::LambdaCall {
-						lambda: Box::new(parser::Value::Lambda {
-							args_def: args_def[..len].to_owned(),
-							content,
-						}),
-
-						args: args_
-							.into_iter()
-							.map(|e| -> parser::ASTNode {
-								parser::ASTNode::Value(eval(e, variables, args))
-							}).collect::<Vec<parser::ASTNode>>(),
-				})}
-			} else {
-				for (index, i) in args_.into_iter().enumerate() {
-					if let Some(ch) = args_def.chars().nth(index) {
-						if new_args.contains_key(&ch) {
-							new_args.remove(&ch);
-						}
+		self.execute(nodes)
+	}
 
-						let _ = new_args.insert(ch, eval(i, variables, args));
-					}
-				}
+	// `run`, without giving the limits back, for the modules a script imports
+	fn execute(&mut self, nodes: Vec<parser::ASTNode>) -> Result<parser::Value, LambisError> {
+		if self.backend == Backend::Vm {
+			return vm::run(vm::compiler::compile(nodes), self);
+		}
 
-				eval(*content, variables, &mut new_args)
-			}
+		let mut result: parser::Value = parser::Value::None;
+		for node in nodes {
+			result = walker::eval(node, self)?;
 		}
 
-		parser::ASTNode::Call {name, args: args_} => {
-			let var_content: parser::Value = eval(parser::ASTNode::Value(parser::Value::Variable(name.clone())), variables, args);
-			if let parser::Value::Lambda {..} = var_content {
-				eval(parser::ASTNode::LambdaCall {
-					lambda: Box::new(var_content), args: args_
-				}, variables, args)
-			} else {
-				panic!("InterpreterError: trying to call «{name}, {var_content:?}»")
+		Ok(result)
+	}
+
+	pub fn eval_source(&mut self, source: &str) -> Result<parser::Value, LambisError> {
+		let nodes: Vec<parser::ASTNode> = parser::parse_file(source, 0, self.limits.nesting())?;
+		self.run(nodes)
+	}
+
+	// evaluates a script, resolving its imports relative to its own location
+	pub fn eval_file(&mut self, path: &Path, source: &str) -> Result<parser::Value, LambisError> {
+		let Ok(resolved) = path.canonicalize() else {
+			return self.eval_source(source);
+		};
+
+		self.modules.enter(&resolved, Span::default())?;
+		let result: Result<parser::Value, LambisError> = self.eval_source(source);
+		self.modules.leave();
+		result
+	}
+
+	pub fn definable(&self, name: &str, span: Span) -> Result<(), LambisError> {
+		if self.defined.contains(name) && !self.redefine {
+			return Err(LambisError::runtime(format!("constant «{name}» is already defined"), span)
+				.with_hint(format!("use `let {name} = ... in ...` for a local binding")));
+		}
+
+		Ok(())
+	}
+
+	pub fn define(&mut self, name: String, value: parser::Value) {
+		self.defined.insert(name.clone());
+		self.variables.insert(name, value);
+	}
+
+	// every function application is where an evaluation can be stopped
+	fn step(&mut self, span: Span) -> Result<(), LambisError> {
+		if self.cancellation.cancelled() {
+			return Err(LambisError::cancelled(span));
+		}
+
+		self.usage.steps += 1;
+		if let (Some(deadline), Some(timeout)) = (self.usage.deadline, self.limits.timeout) {
+			if self.usage.steps.is_multiple_of(CLOCK_EVERY) && Instant::now() >= deadline {
+				return Err(LambisError::cancelled(span).with_hint(format!("it ran for longer than {timeout:?}")));
 			}
 		}
 
-		parser::ASTNode::Operation{left: left_, operation: op, right: right_} => {
-			let left: parser::Value = eval(*left_, variables, args);
-			let right: parser::Value = eval(*right_, variables, args);
-			operation(&op, left, right)
+		within(self.usage.steps, self.limits.steps, Limit::Steps, span)
+	}
+
+	fn enter(&mut self, span: Span) -> Result<(), LambisError> {
+		self.usage.depth += 1;
+		within(self.usage.depth, self.limits.depth(), Limit::Depth, span)
+	}
+
+	fn leave(&mut self) {
+		self.usage.depth -= 1;
+	}
+
+	fn allocate(&mut self, values: u64, span: Span) -> Result<(), LambisError> {
+		self.usage.values += values;
+		within(self.usage.values, self.limits.values, Limit::Values, span)
+	}
+
+	// `what` is only allowed outside of the sandbox
+	fn permit(&self, what: &str, span: Span) -> Result<(), LambisError> {
+		if self.limits.sandbox {
+			return Err(LambisError::runtime(format!("{what} is disabled in the sandbox"), span));
 		}
+
+		Ok(())
+	}
+
+	// finds the file an error points into, when it comes from an imported module
+	pub fn source_of(&self, error: &LambisError) -> Option<(String, &str)> {
+		self.modules
+			.source(error.span().file)
+			.map(|(path, source): (&Path, &str)| (path.display().to_string(), source))
 	}
 }
 
-pub fn eval_start(s: &str) -> Result<(), String> {
-	let p: Vec<parser::ASTNode> = parser::parse(s)?;
-	let mut variables: HashMap<String, parser::Value> = HashMap::new();
-	let mut arguments: HashMap<char, parser::Value> = HashMap::new();
+#[cfg(test)]
+mod tests {
+	use std::{env, fs, path::PathBuf, process};
 
-	variables.insert("true".to_owned(), parser::Value::Integer(1));
-	variables.insert("false".to_owned(), parser::Value::Integer(0));
+	use super::{integer_part, parser::Value, read_line, Backend, BigInt, Environment, LambisError, Span};
 
-	for i in p {
-		eval(i, &mut variables, &mut arguments);
+	fn eval_with(backend: Backend, source: &str) -> Result<Value, LambisError> {
+		let mut env: Environment = Environment::new(env::temp_dir());
+		env.backend = backend;
+		env.eval_source(source)
 	}
 
-	Ok(())
+	// evaluates `source` on both backends, which have to agree on the result
+	fn eval(source: &str) -> Result<Value, LambisError> {
+		let [walked, compiled]: [Result<Value, LambisError>; 2] = [Backend::TreeWalker, Backend::Vm]
+			.map(|backend: Backend| eval_with(backend, source));
+
+		assert_eq!(walked, compiled, "the backends disagree on:\n{source}");
+		walked
+	}
+
+	#[test]
+	fn integer_part_of_decimals() {
+		assert_eq!(integer_part(Value::Decimal(2.5), Span::default()).unwrap(), Value::Integer(2));
+		assert_eq!(integer_part(Value::Decimal(-2.5), Span::default()).unwrap(), Value::Integer(-3));
+		assert_eq!(integer_part(Value::Decimal(1e40), Span::default()).unwrap(), Value::BigInt(BigInt::from_f64(1e40).unwrap()));
+	}
+
+	#[test]
+	fn integer_part_of_non_finite_decimals_is_an_error() {
+		for n in [f64::INFINITY, f64::NEG_INFINITY, f64::NAN] {
+			let error: LambisError = integer_part(Value::Decimal(n), Span::default()).unwrap_err();
+			assert_eq!(error.message(), format!("cannot take the integer part of {n}"));
+		}
+	}
+
+	#[test]
+	fn functions_are_equal_wherever_they_are_written() {
+		assert_eq!(eval("f = λx. (x + y).\ng =   λx.\n\t(x+y).\ng != f"), Ok(Value::Bool(false)));
+		assert_eq!(eval("f = λx. (x + y).\ng = λx. (x + z).\ng != f"), Ok(Value::Bool(true)));
+	}
+
+	#[test]
+	fn input_is_read_a_line_at_a_time() {
+		let mut input: &[u8] = b"hello world\r\n  42 \n-2.5\n\n";
+		assert_eq!(read_line(&mut input, false, Span::default()), Ok(Value::String("hello world".to_owned())));
+		assert_eq!(read_line(&mut input, true, Span::default()), Ok(Value::Integer(42)));
+		assert_eq!(read_line(&mut input, true, Span::default()), Ok(Value::Decimal(-2.5)));
+		assert_eq!(read_line(&mut input, false, Span::default()), Ok(Value::String(String::new())));
+		assert_eq!(read_line(&mut input, false, Span::default()), Ok(Value::None));
+		assert_eq!(read_line(&mut input, true, Span::default()), Ok(Value::None));
+	}
+
+	#[test]
+	fn numeric_input_has_to_be_a_number() {
+		let mut input: &[u8] = b"123456789012345678901234567890123456789012\nforty two\n";
+		assert_eq!(
+			read_line(&mut input, true, Span::default()),
+			Ok(Value::BigInt(BigInt::parse("123456789012345678901234567890123456789012").unwrap())),
+		);
+
+		let error: LambisError = read_line(&mut input, true, Span::default()).unwrap_err();
+		assert_eq!(error.message(), "expected a number as input, got «forty two»");
+	}
+
+	#[test]
+	fn closures_capture_the_bindings_they_see() {
+		assert_eq!(eval("adder = λx. λy. x + y.\nadd2 = (adder 2).\n(add2 3)"), Ok(Value::Integer(5)));
+		let error: LambisError = eval("adders = '((adder 1) (adder 2)).\nadder = λx. λy. x + y.").unwrap_err();
+		assert_eq!(error.message(), "variable «adder» is undefined in current context");
+		assert_eq!(eval("adder = λx. λy. x + y.\nadders = '((adder 1) (adder 2)).\nsecond = (adders !! 1).\n(second 10)"), Ok(Value::Integer(12)));
+	}
+
+	#[test]
+	fn captured_bindings_are_not_looked_up_where_the_closure_is_called() {
+		assert_eq!(eval("k = λx. λy. x.\none = (k 1).\ng = λx. (one 0).\n(g 5)"), Ok(Value::Integer(1)));
+		assert_eq!(eval("h = let a = 10 in λy. a + y.\na = (100).\n(h 1)"), Ok(Value::Integer(11)));
+	}
+
+	#[test]
+	fn globals_are_looked_up_when_the_closure_runs() {
+		assert_eq!(eval("f = λx. (later x).\nlater = λx. (x * 3).\n(f 2)"), Ok(Value::Integer(6)));
+	}
+
+	#[test]
+	fn inexact_divisions_are_rationals() {
+		assert_eq!(eval("1/3 + 1/6").map(|value: Value| value.to_string()), Ok("1/2".to_owned()));
+		assert_eq!(eval("6 / (0 - 4)").map(|value: Value| value.to_string()), Ok("-3/2".to_owned()));
+		assert_eq!(eval("2/4 * 2"), Ok(Value::Integer(1)));
+		assert_eq!(eval("(1/3) = (2/6)"), Ok(Value::Bool(true)));
+		assert_eq!(eval("[7/2]"), Ok(Value::Integer(3)));
+	}
+
+	#[test]
+	fn division_by_zero_is_an_error() {
+		for source in ["1 / 0", "1/2 / 0", "1 / (1/2 - 1/2)", "100000000000000000000000000000000000000000 / 0"] {
+			assert_eq!(eval(source).unwrap_err().message(), "division by zero", "{source}");
+		}
+	}
+
+	#[test]
+	fn negative_exponents_are_exact() {
+		let shown = |source: &str| eval(source).map(|value: Value| value.to_string());
+		assert_eq!(shown("2 ^ (0 - 2)"), Ok("1/4".to_owned()));
+		assert_eq!(shown("(1/2) ^ (0 - 3)"), Ok("8".to_owned()));
+		assert_eq!(shown("(2/3) ^ 2"), Ok("4/9".to_owned()));
+		assert_eq!(shown("(0 - 2) ^ 3"), Ok("-8".to_owned()));
+		assert_eq!(shown("2 ^ 100"), Ok("1267650600228229401496703205376".to_owned()));
+		assert_eq!(eval("0 ^ (0 - 1)").unwrap_err().message(), "division by zero");
+	}
+
+	#[test]
+	fn rational_exponents_are_decimals() {
+		assert_eq!(eval("4 ^ (1/2)"), Ok(Value::Decimal(2.0)));
+		assert_eq!(eval("(1/4) ^ (1/2)"), Ok(Value::Decimal(0.5)));
+		assert_eq!(eval("2.0 ^ (0 - 1)"), Ok(Value::Decimal(0.5)));
+	}
+
+	#[test]
+	fn huge_exponents_are_refused() {
+		assert_eq!(eval("10 ^ 1000000").unwrap_err().message(), "exponent 1000000 is out of range, the result would have about 1000000 digits");
+		assert_eq!(eval("2 ^ 100000000000000000000000").unwrap_err().message(), "exponent 100000000000000000000000 is out of range");
+	}
+
+	#[test]
+	fn floor_division_and_remainder_take_the_sign_of_the_divisor() {
+		for (left, right, quotient, remainder) in [("7", "2", 3, 1), ("(0 - 7)", "2", -4, 1), ("7", "(0 - 2)", -4, -1), ("(0 - 7)", "(0 - 2)", 3, -1)] {
+			assert_eq!(eval(&format!("{left} // {right}")), Ok(Value::Integer(quotient)));
+			assert_eq!(eval(&format!("{left} % {right}")), Ok(Value::Integer(remainder)));
+		}
+
+		assert_eq!(eval("100000000000000000000000000000000000000000 % (0 - 3)"), Ok(Value::Integer(-2)));
+		assert_eq!(eval("(7/2) // (1/3)"), Ok(Value::Integer(10)));
+		assert_eq!(eval("(7/2) % (1/3)").map(|value: Value| value.to_string()), Ok("1/6".to_owned()));
+		assert_eq!(eval("(0 - 7.5) % 2"), Ok(Value::Decimal(0.5)));
+	}
+
+	#[test]
+	fn exact_division_by_zero_is_an_error_for_every_operator() {
+		for op in ["/", "//", "%"] {
+			for left in ["1", "(1/2)", "100000000000000000000000000000000000000000"] {
+				assert_eq!(eval(&format!("{left} {op} 0")).unwrap_err().message(), "division by zero");
+			}
+		}
+	}
+
+	#[test]
+	fn decimal_division_by_zero_follows_floats() {
+		assert_eq!(eval("1.5 / 0"), Ok(Value::Decimal(f64::INFINITY)));
+		assert_eq!(eval("(0 - 1.5) // 0"), Ok(Value::Decimal(f64::NEG_INFINITY)));
+		for backend in [Backend::TreeWalker, Backend::Vm] {
+			assert!(matches!(eval_with(backend, "1.5 % 0"), Ok(Value::Decimal(n)) if n.is_nan()));
+		}
+	}
+
+	// `logged s` appends `s` to a file each time it is evaluated, and returns it
+	fn counted(name: &str, source: &str) {
+		for backend in [Backend::TreeWalker, Backend::Vm] {
+			let log: PathBuf = env::temp_dir().join(format!("lambis-{name}-{}.txt", process::id()));
+			let logged: String = format!("logged = λs. (fileappend {:?} s) ${{ _ -> s }}.\n", log.display().to_string());
+			let result: Result<Value, LambisError> = eval_with(backend, &(logged + source));
+			let evaluated: String = fs::read_to_string(&log).unwrap_or_default();
+			let _ = fs::remove_file(&log);
+
+			assert_eq!((result, evaluated.as_str()), (Ok(Value::String("xxx".to_owned())), "x"), "{source}");
+		}
+	}
+
+	#[test]
+	fn lazy_arguments_are_evaluated_once() {
+		counted("lazy-once", "thrice = λ~x. x ++ x ++ x.\n(thrice (logged \"x\"))");
+		counted("lazy-shared", "twice = λ~x. x ++ x.\nouter = λ~x. (twice x) ++ x.\n(outer (logged \"x\"))");
+		counted("lazy-eager", "id = λx. x.\nthrice = λ~x. (id x) ++ x ++ (id x).\n(thrice (logged \"x\"))");
+	}
+
+	#[test]
+	fn unused_lazy_arguments_are_never_evaluated() {
+		assert_eq!(eval("if = λc~t~f. c ${ true -> t  false -> f }.\n(if true 1 (1/0))"), Ok(Value::Integer(1)));
+		assert_eq!(eval("ignore = λ~x. \"ignored\".\n(ignore (undefined 1))"), Ok(Value::String("ignored".to_owned())));
+		assert_eq!(eval("if = λc~t~f. c ${ true -> t  false -> f }.\n(if false 1 (1/0))").unwrap_err().message(), "division by zero");
+	}
+
+	#[test]
+	fn calls_waiting_for_each_other_fit_on_a_test_thread() {
+		let sum: &str = "sum = λn. n ${\n\t0 -> 0\n\t_ -> n + (sum n-1)\n}.\n";
+		assert_eq!(eval(format!("{sum}(sum 100000)").as_str()), Ok(Value::Integer(5000050000)));
+
+		let lazy: &str = "count = λn~a. n ${\n\t0 -> a\n\t_ -> (count n-1 a + 1)\n}.\n";
+		assert_eq!(eval(format!("{lazy}(count 20000 0)").as_str()), Ok(Value::Integer(20000)));
+	}
+
+	#[test]
+	fn named_parameters_shadow_globals() {
+		let map: &str = "map = λ(list fn). list ${ '() -> '()  (h . t) -> (fn h) : (map t fn) }.\n";
+		assert_eq!(eval(&format!("{map}list = (0).\nfn = (0).\n(map '(1 2) λx. x * 2)")), Ok(Value::Array(vec![Value::Integer(2), Value::Integer(4)])));
+		assert_eq!(eval("x = (5).\nf = λ(x y). x + y.\n(f 1 2)"), Ok(Value::Integer(3)));
+		assert_eq!(eval("if = λ(cond ~then ~else). cond ${ true -> then  false -> else }.\n(if false (1/0) \"no\")"), Ok(Value::String("no".to_owned())));
+	}
+
+	#[test]
+	fn definitions_can_shadow_builtins() {
+		assert_eq!(eval("not = λa. 1 - a.\n(not 1)"), Ok(Value::Integer(0)));
+		assert_eq!(eval("decimal = \"mine\".\ntrue = (0).\ndecimal ++ \"\""), Ok(Value::String("mine".to_owned())));
+	}
+
+	#[test]
+	fn definitions_are_constants() {
+		for (source, name, line) in [
+			("x = (1).\nx = (2).", "x", 2),
+			("not = λa. a.\nnot = λa. a.", "not", 2),
+			("f = λx. x.\ng = (f 1).\nf = λx. x.", "f", 3),
+		] {
+			let error: LambisError = eval(source).unwrap_err();
+			assert_eq!(error.message(), format!("constant «{name}» is already defined"));
+			assert_eq!(error.span().line, line);
+		}
+	}
+
+	#[test]
+	fn redefining_can_be_allowed() {
+		for backend in [Backend::TreeWalker, Backend::Vm] {
+			let mut env: Environment = Environment::new(env::temp_dir());
+			env.backend = backend;
+			env.redefine = true;
+			assert_eq!(env.eval_source("x = (1).\nx = (2).\nx"), Ok(Value::Integer(2)));
+			assert_eq!(env.eval_source("x = \"again\"."), Ok(Value::String("again".to_owned())));
+		}
+	}
 }
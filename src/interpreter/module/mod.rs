@@ -0,0 +1,315 @@
+use std::{
+	collections::{HashMap, HashSet},
+	fs,
+	path::{Path, PathBuf},
+};
+
+use super::{
+	error::{LambisError, Span},
+	parser::{pattern::{Case, Pattern}, ASTNode, Parameter, Value},
+};
+
+pub struct Module {
+	pub namespace: String,
+	pub names: Vec<String>,
+}
+
+pub struct Modules {
+	root: PathBuf,
+	loaded: HashMap<PathBuf, Module>,
+	loading: Vec<PathBuf>,
+	sources: Vec<(PathBuf, String)>,
+}
+
+impl Modules {
+	pub fn new(root: PathBuf) -> Self {
+		Self {
+			root,
+			loaded: HashMap::new(),
+			loading: vec![],
+			sources: vec![],
+		}
+	}
+
+	// imports are resolved relative to the module being loaded, or to the root otherwise
+	pub fn resolve(&self, path: &str, span: Span) -> Result<PathBuf, LambisError> {
+		let base: &Path = self.loading
+			.last()
+			.and_then(|current: &PathBuf| current.parent())
+			.unwrap_or(self.root.as_path());
+
+		base.join(path)
+			.canonicalize()
+			.map_err(|error| LambisError::runtime(format!("cannot import «{path}»: {error}"), span))
+	}
+
+	pub fn get(&self, path: &Path) -> Option<&Module> {
+		self.loaded.get(path)
+	}
+
+	pub fn enter(&mut self, path: &Path, span: Span) -> Result<(), LambisError> {
+		if let Some(index) = self.loading.iter().position(|loading: &PathBuf| loading == path) {
+			let cycle: Vec<String> = self.loading[index..]
+				.iter()
+				.chain([path.to_path_buf()].iter())
+				.map(|p: &PathBuf| p.display().to_string())
+				.collect();
+
+			return Err(LambisError::runtime(format!("import cycle: {}", cycle.join(" → ")), span));
+		}
+
+		self.loading.push(path.to_path_buf());
+		Ok(())
+	}
+
+	pub fn leave(&mut self) {
+		let _: Option<PathBuf> = self.loading.pop();
+	}
+
+	pub fn finish(&mut self, path: PathBuf, module: Module) {
+		self.loaded.insert(path, module);
+	}
+
+	// reads a module and registers its source, returning the file id used in its spans
+	pub fn read(&mut self, path: &Path, span: Span) -> Result<(usize, String), LambisError> {
+		let source: String = fs::read_to_string(path)
+			.map_err(|error| LambisError::runtime(format!("cannot import «{}»: {error}", path.display()), span))?;
+
+		self.sources.push((path.to_path_buf(), source.clone()));
+		Ok((self.sources.len(), source))
+	}
+
+	pub fn source(&self, file: usize) -> Option<(&Path, &str)> {
+		file.checked_sub(1)
+			.and_then(|index: usize| self.sources.get(index))
+			.map(|(path, source): &(PathBuf, String)| (path.as_path(), source.as_str()))
+	}
+}
+
+pub fn default_namespace(path: &Path) -> String {
+	path.file_stem()
+		.map(|stem| stem.to_string_lossy().into_owned())
+		.unwrap_or_default()
+}
+
+pub fn definitions(nodes: &[ASTNode]) -> Vec<String> {
+	nodes.iter()
+		.filter_map(|node: &ASTNode| match node {
+			ASTNode::Definition {name, ..} => Some(name.clone()),
+			_ => None,
+		}).collect()
+}
+
+struct Qualifier<'a> {
+	names: &'a HashSet<String>,
+	namespace: &'a str,
+	bound: Vec<String>,
+}
+
+impl Qualifier<'_> {
+	fn name(&self, name: String) -> String {
+		if self.names.contains(&name) && !self.bound.contains(&name) {
+			format!("{}::{name}", self.namespace)
+		} else {
+			name
+		}
+	}
+
+	fn scoped<T>(&mut self, names: impl Iterator<Item=String>, f: impl FnOnce(&mut Self) -> T) -> T {
+		let len: usize = self.bound.len();
+		self.bound.extend(names);
+		let result: T = f(self);
+		self.bound.truncate(len);
+		result
+	}
+
+	fn value(&mut self, value: Value) -> Value {
+		match value {
+			Value::Variable(name, span) => Value::Variable(self.name(name), span),
+			Value::Lambda {args_def, content, captured} => {
+				let params: Vec<String> = args_def.iter().map(|param: &Parameter| param.name.clone()).collect();
+				Value::Lambda {
+					content: Box::new(self.scoped(params.into_iter(), |q: &mut Self| q.node(*content))),
+					args_def,
+					captured,
+				}
+			}
+
+			Value::Array(items) => Value::Array(items.into_iter().map(|i: Value| self.value(i)).collect()),
+			Value::Builtin {builtin, applied} => Value::Builtin {
+				builtin,
+				applied: applied.into_iter().map(|i: Value| self.value(i)).collect(),
+			},
+
+			other => other,
+		}
+	}
+
+	fn nodes(&mut self, nodes: Vec<ASTNode>) -> Vec<ASTNode> {
+		nodes.into_iter().map(|node: ASTNode| self.node(node)).collect()
+	}
+
+	fn boxed(&mut self, node: ASTNode) -> Box<ASTNode> {
+		Box::new(self.node(node))
+	}
+
+	fn node(&mut self, node: ASTNode) -> ASTNode {
+		match node {
+			ASTNode::Value(value) => ASTNode::Value(self.value(value)),
+			ASTNode::Array(items, span) => ASTNode::Array(self.nodes(items), span),
+
+			ASTNode::Definition {name, value, span} => ASTNode::Definition {
+				name: self.name(name),
+				value: self.boxed(*value),
+				span,
+			},
+
+			ASTNode::Let {name, value, body} => {
+				let value: Box<ASTNode> = self.boxed(*value);
+				let body: Box<ASTNode> = Box::new(self.scoped([name.clone()].into_iter(), |q: &mut Self| q.node(*body)));
+				ASTNode::Let {name, value, body}
+			}
+
+			ASTNode::LambdaCall {lambda, args, span} => ASTNode::LambdaCall {
+				lambda: Box::new(self.value(*lambda)),
+				args: self.nodes(args),
+				span,
+			},
+
+			ASTNode::Call {name, args, span} => ASTNode::Call {
+				name: self.name(name),
+				args: self.nodes(args),
+				span,
+			},
+
+			ASTNode::Switch {compared, cases} => ASTNode::Switch {
+				compared: self.boxed(*compared),
+				cases: cases
+					.into_iter()
+					.map(|case: Case| {
+						// pins see the scope of the switch, not what the pattern binds
+						let pattern: Pattern = case.pattern.map_pins(&mut |pin: ASTNode| self.node(pin));
+						self.scoped(pattern.names().into_iter(), |q: &mut Self| Case {
+							guard: case.guard.map(|guard: ASTNode| q.node(guard)),
+							action: q.node(case.action),
+							pattern,
+							span: case.span,
+						})
+					}).collect(),
+			},
+
+			ASTNode::RationalPart(value, span) => ASTNode::RationalPart(self.boxed(*value), span),
+			ASTNode::IntegerPart(value, span) => ASTNode::IntegerPart(self.boxed(*value), span),
+			ASTNode::Length(value, span) => ASTNode::Length(self.boxed(*value), span),
+			ASTNode::Print(value, span) => ASTNode::Print(self.boxed(*value), span),
+
+			ASTNode::Operation {left, operation, right, span} => ASTNode::Operation {
+				left: self.boxed(*left),
+				operation,
+				right: self.boxed(*right),
+				span,
+			},
+
+			other @ (ASTNode::Nothing | ASTNode::Import {..} | ASTNode::Input {..}) => other,
+		}
+	}
+}
+
+// prefixes every free reference to one of the module's own definitions with its namespace,
+// so that module code keeps working once its definitions live under `namespace::name`
+pub fn qualify(node: ASTNode, names: &HashSet<String>, namespace: &str) -> ASTNode {
+	Qualifier {
+		names,
+		namespace,
+		bound: vec![],
+	}.node(node)
+}
+
+#[cfg(test)]
+mod tests {
+	use std::{env, fs, path::{Path, PathBuf}, process};
+
+	use crate::interpreter::{parser::Value, Backend, Environment, LambisError};
+
+	// a fresh directory holding `files`
+	fn project(name: &str, files: &[(&str, &str)]) -> PathBuf {
+		let root: PathBuf = env::temp_dir().join(format!("lambis-{name}-{}", process::id()));
+		let _ = fs::remove_dir_all(&root);
+		fs::create_dir_all(&root).unwrap();
+		for (file, source) in files {
+			fs::write(root.join(file), source).unwrap();
+		}
+
+		root.canonicalize().unwrap()
+	}
+
+	fn eval(root: &Path, backend: Backend, source: &str) -> Result<Value, LambisError> {
+		let mut env: Environment = Environment::new(root.to_path_buf());
+		env.backend = backend;
+		env.eval_source(source)
+	}
+
+	#[test]
+	fn definitions_are_imported_into_their_namespace() {
+		let root: PathBuf = project("namespaces", &[
+			("math.li", "double = λx. (x * 2).\nquad = λx. (double (double x)).\n"),
+		]);
+
+		for backend in [Backend::TreeWalker, Backend::Vm] {
+			let source: &str = "import \"math.li\".\nimport \"math.li\" as m.\n(math::quad 3) + (m::double 1)";
+			assert_eq!(eval(&root, backend, source), Ok(Value::Integer(14)));
+
+			let error: LambisError = eval(&root, backend, "import \"math.li\" as m.\n(double 1)").unwrap_err();
+			assert_eq!(error.message(), "variable «double» is undefined in current context");
+		}
+
+		fs::remove_dir_all(&root).unwrap();
+	}
+
+	#[test]
+	fn pins_refer_to_the_module_definitions() {
+		let root: PathBuf = project("pins", &[
+			("origin.li", "zero = 0
+is = λx. x ${\n\t=zero -> true\n\t_ -> false\n}.\n"),
+		]);
+
+		for backend in [Backend::TreeWalker, Backend::Vm] {
+			assert_eq!(eval(&root, backend, "import \"origin.li\" as o.\n(o::is 0)"), Ok(Value::Bool(true)));
+			assert_eq!(eval(&root, backend, "import \"origin.li\" as o.\n(o::is 1)"), Ok(Value::Bool(false)));
+		}
+
+		fs::remove_dir_all(&root).unwrap();
+	}
+
+	#[test]
+	fn modules_are_evaluated_once() {
+		let root: PathBuf = project("cached", &[]);
+		let log: PathBuf = root.join("log.txt");
+		fs::write(root.join("loud.li"), format!("logged = (fileappend {:?} \"loaded\\n\").\n", log.display().to_string())).unwrap();
+
+		for backend in [Backend::TreeWalker, Backend::Vm] {
+			let source: &str = "import \"loud.li\".\nimport \"./loud.li\".\nimport \"loud.li\" as again.\nagain::logged";
+			assert_eq!(eval(&root, backend, source), Ok(Value::None));
+			assert_eq!(fs::read_to_string(&log).unwrap(), "loaded\n");
+			fs::remove_file(&log).unwrap();
+		}
+
+		fs::remove_dir_all(&root).unwrap();
+	}
+
+	#[test]
+	fn import_cycles_are_errors() {
+		let root: PathBuf = project("cycle", &[
+			("a.li", "import \"b.li\".\n"),
+			("b.li", "import \"a.li\".\n"),
+		]);
+
+		let (a, b): (String, String) = (root.join("a.li").display().to_string(), root.join("b.li").display().to_string());
+		for backend in [Backend::TreeWalker, Backend::Vm] {
+			let error: LambisError = eval(&root, backend, "import \"a.li\".").unwrap_err();
+			assert_eq!(error.message(), format!("import cycle: {a} → {b} → {a}"));
+		}
+
+		fs::remove_dir_all(&root).unwrap();
+	}
+}
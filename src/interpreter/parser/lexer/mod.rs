@@ -1,5 +1,58 @@
-use std::{iter::Peekable, str::Chars};
-type Lexable<'a> = Peekable<Chars<'a>>;
+use std::{fmt, iter::Peekable, str::Chars};
+use crate::interpreter::error::{LambisError, Span};
+
+#[derive(Clone)]
+struct Lexable<'a> {
+	chars: Peekable<Chars<'a>>,
+	offset: usize,
+	line: usize,
+	column: usize,
+	file: usize,
+}
+
+impl<'a> Lexable<'a> {
+	fn new(source: &'a str, file: usize) -> Self {
+		Self {
+			chars: source.chars().peekable(),
+			offset: 0,
+			line: 1,
+			column: 1,
+			file,
+		}
+	}
+
+	fn peek(&mut self) -> Option<&char> {
+		self.chars.peek()
+	}
+
+	fn location(&self) -> Span {
+		Span {
+			start: self.offset,
+			end: self.offset,
+			line: self.line,
+			column: self.column,
+			file: self.file,
+		}
+	}
+}
+
+impl Iterator for Lexable<'_> {
+	type Item = char;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		let ch: char = self.chars.next()?;
+		self.offset += ch.len_utf8();
+
+		if ch == '\n' {
+			self.line += 1;
+			self.column = 1;
+		} else {
+			self.column += 1;
+		}
+
+		Some(ch)
+	}
+}
 
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub enum Token {
@@ -10,12 +63,21 @@ pub enum Token {
 	OpenBracket,   // [
 	CloseBracket,  // ]
 	Exclam,        // !
+	DoubleExclam,  // !!
+	Question,      // ?
+	DoubleQuestion, // ??
 	Bar,           // |
 	Underscore,    // _
+	Tilde,         // ~
 	Plus,          // +
+	DoublePlus,    // ++
 	Minus,         // -
 	Asterisk,      // *
 	Slash,         // /
+	DoubleSlash,   // //
+	Percent,       // %
+	DoubleAmpersand, // &&
+	DoubleBar,     // ||
 	Circumflex,    // ^
 	Equal,         // =
 	NotEqual,      // !=
@@ -26,40 +88,141 @@ pub enum Token {
 	Arrow,         // ->
 	Lambda,        // λ
 	Period,        // .
+	Colon,         // :
 	Dollar,        // $
-    Ident(String), // [a-zA-Z0-9]+
+	Quote,         // '(
+	Let,           // let
+	In,            // in
+	Import,        // import
+	As,            // as
+	When,          // when
+    Ident(String), // [a-zA-Z0-9]+(::[a-zA-Z][a-zA-Z0-9]*)*
     Word(String),  // '[a-zA-Z0-9]+
-	Integer(i128), // [0-9]+
+	String(String), // ".*"
+	Integer(String), // [0-9]+
+}
+
+impl Token {
+	pub const KEYWORDS: [Token; 5] = [Token::Let, Token::In, Token::Import, Token::As, Token::When];
+
+	pub fn is_keyword(&self) -> bool {
+		Self::KEYWORDS.contains(self)
+	}
+}
+
+// how the token is written in the source, for error messages
+impl fmt::Display for Token {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		let text: &str = match self {
+			Token::OpenParen => "(",
+			Token::CloseParen => ")",
+			Token::OpenBrace => "{",
+			Token::CloseBrace => "}",
+			Token::OpenBracket => "[",
+			Token::CloseBracket => "]",
+			Token::Exclam => "!",
+			Token::DoubleExclam => "!!",
+			Token::Question => "?",
+			Token::DoubleQuestion => "??",
+			Token::Bar => "|",
+			Token::Underscore => "_",
+			Token::Tilde => "~",
+			Token::Plus => "+",
+			Token::DoublePlus => "++",
+			Token::Minus => "-",
+			Token::Asterisk => "*",
+			Token::Slash => "/",
+			Token::DoubleSlash => "//",
+			Token::Percent => "%",
+			Token::DoubleAmpersand => "&&",
+			Token::DoubleBar => "||",
+			Token::Circumflex => "^",
+			Token::Equal => "=",
+			Token::NotEqual => "!=",
+			Token::Less => "<",
+			Token::Greater => ">",
+			Token::LessEqual => "<=",
+			Token::GreaterEqual => ">=",
+			Token::Arrow => "->",
+			Token::Lambda => "λ",
+			Token::Period => ".",
+			Token::Colon => ":",
+			Token::Dollar => "$",
+			Token::Quote => "'(",
+			Token::Let => "let",
+			Token::In => "in",
+			Token::Import => "import",
+			Token::As => "as",
+			Token::When => "when",
+			Token::Ident(name) | Token::Integer(name) => name,
+			Token::Word(word) => return write!(f, "'{word}"),
+			Token::String(string) => return write!(f, "{string:?}"),
+		};
+
+		f.write_str(text)
+	}
+}
+
+// a comment together with its `#` or `##` markers, the parser never sees them
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Comment {
+	pub text: String,
+	pub span: Span,
 }
 
 trait LexableExt<'a> {
+	fn lex_name(&mut self) -> String;
 	fn lex_ident(&mut self) -> Token;
     fn lex_word(&mut self) -> Token;
 	fn lex_integer(&mut self) -> Token;
-	fn lex_multiline_comment(&mut self);
-	fn lex_comment(&mut self);
+	fn lex_escape(&mut self) -> Result<char, LambisError>;
+	fn lex_string(&mut self) -> Result<Token, LambisError>;
+	fn lex_multiline_comment(&mut self, text: &mut String);
+	fn lex_comment(&mut self) -> String;
 	fn lex_spaces(&mut self);
-	fn lex(&mut self) -> Result<Vec<Token>, String>;
+	fn lex(&mut self, comments: &mut Vec<Comment>) -> Result<Vec<(Token, Span)>, LambisError>;
 }
 
 
 impl LexableExt<'_> for Lexable<'_> {
-	fn lex_ident(&mut self) -> Token {
+	fn lex_name(&mut self) -> String {
 		let mut result: String = String::new();
 
-		while self.peek().is_some_and(char::is_ascii_alphanumeric) {
-			result.push(self.next().unwrap());
+		loop {
+			while self.peek().is_some_and(char::is_ascii_alphanumeric) {
+				result.push(self.next().unwrap());
+			}
+
+			// namespaced names like `array::map`
+			let mut clone: Lexable = self.clone();
+			if clone.next() != Some(':') || clone.next() != Some(':') || !clone.peek().is_some_and(char::is_ascii_alphabetic) {
+				break;
+			}
+
+			result.push_str("::");
+			let _: Option<char> = self.next();
+			let _: Option<char> = self.next();
 		}
 
-		Token::Ident(result)
+		result
+	}
+
+	// keywords can't be names, but words like `'in` are fine
+	fn lex_ident(&mut self) -> Token {
+		let name: String = self.lex_name();
+		Token::KEYWORDS
+			.into_iter()
+			.find(|keyword: &Token| keyword.to_string() == name)
+			.unwrap_or(Token::Ident(name))
 	}
 
     fn lex_word(&mut self) -> Token {
         let _: Option<char> = self.next();
-        let Token::Ident(s) = self.lex_ident() else {
-            unreachable!("urmom");
-        };
-        Token::Word(s)
+        if self.peek() == Some(&'(') {
+            return Token::Quote;
+        }
+
+        Token::Word(self.lex_name())
     }
 
 	fn lex_integer(&mut self) -> Token {
@@ -69,7 +232,61 @@ impl LexableExt<'_> for Lexable<'_> {
 			temporary.push(self.next().unwrap());
 		}
 
-		Token::Integer(temporary.parse::<i128>().unwrap())
+		Token::Integer(temporary)
+	}
+
+	fn lex_escape(&mut self) -> Result<char, LambisError> {
+		let start: Span = self.location();
+		let ch: char = self.next().ok_or_else(||
+			LambisError::lex("unterminated escape sequence", start)
+		)?;
+
+		Ok(match ch {
+			'n' => '\n',
+			't' => '\t',
+			'r' => '\r',
+			'0' => '\0',
+			'"' => '"',
+			'\\' => '\\',
+			'u' => {
+				if self.next() != Some('{') {
+					return Err(LambisError::lex("expected «{» after «\\u»", start.to(self.location())));
+				}
+
+				let mut code: String = String::new();
+				while self.peek().is_some_and(char::is_ascii_hexdigit) {
+					code.push(self.next().unwrap());
+				}
+
+				if self.next() != Some('}') {
+					return Err(LambisError::lex("expected «}» after unicode escape", start.to(self.location())));
+				}
+
+				u32::from_str_radix(code.as_str(), 16)
+					.ok()
+					.and_then(char::from_u32)
+					.ok_or_else(|| LambisError::lex(format!("invalid unicode escape «\\u{{{code}}}»"), start.to(self.location())))?
+			}
+
+			what => return Err(LambisError::lex(format!("unknown escape sequence «\\{what}»"), start.to(self.location()))),
+		})
+	}
+
+	fn lex_string(&mut self) -> Result<Token, LambisError> {
+		let start: Span = self.location();
+		let _: Option<char> = self.next();
+		let mut result: String = String::new();
+
+		loop {
+			match self.next() {
+				Some('"') => break,
+				Some('\\') => result.push(self.lex_escape()?),
+				Some(ch) => result.push(ch),
+				None => return Err(LambisError::lex("unterminated string literal", start.to(self.location()))),
+			}
+		}
+
+		Ok(Token::String(result))
 	}
 
 	fn lex_spaces(&mut self) {
@@ -78,41 +295,57 @@ impl LexableExt<'_> for Lexable<'_> {
 		}
 	}
 
-	fn lex_multiline_comment(&mut self) {
-        self.next();
-		while self.peek().is_some() && !(self.next().unwrap() == '#' && self.peek().is_some_and(|&ch: &char| ch == '#')) {
-            let _: Option<char> = self.next();
+	// runs until the closing `##`, or the end of the input
+	fn lex_multiline_comment(&mut self, text: &mut String) {
+		text.push(self.next().unwrap());
+		while let Some(ch) = self.next() {
+			text.push(ch);
+			if ch == '#' && self.peek() == Some(&'#') {
+				text.push(self.next().unwrap());
+				return;
+			}
 		}
 	}
 
-	fn lex_comment(&mut self) {
-		let _: Option<char> = self.next();
+	fn lex_comment(&mut self) -> String {
+		let mut text: String = String::from(self.next().unwrap());
 		if self.peek().is_some_and(|&ch: &char| ch == '#') {
-			self.lex_multiline_comment();
+			self.lex_multiline_comment(&mut text);
 		} else {
 			while self.peek().is_some_and(|&ch: &char| ch != '\n') {
-				let _: Option<char> = self.next();
+				text.push(self.next().unwrap());
 			}
 		}
+
+		text
 	}
 
-	fn lex(&mut self) -> Result<Vec<Token>, String> {
-		let mut result: Vec<Token> = vec![];
+	fn lex(&mut self, comments: &mut Vec<Comment>) -> Result<Vec<(Token, Span)>, LambisError> {
+		let mut result: Vec<(Token, Span)> = vec![];
 
 		while let Some(&ch) = self.peek() {
-			if ch.is_ascii_whitespace() {
+			let start: Span = self.location();
+
+			let token: Token = if ch.is_ascii_whitespace() {
 				self.lex_spaces();
+				continue;
 			} else if ch.is_ascii_alphabetic() {
-				result.push(self.lex_ident());
+				self.lex_ident()
 			} else if ch.is_ascii_digit() {
-				result.push(self.lex_integer());
+				self.lex_integer()
 			} else if ch == '#' {
-				self.lex_comment();
+				let text: String = self.lex_comment();
+				comments.push(Comment {text, span: start.to(self.location())});
+				continue;
+			} else if ch == '\'' {
+				self.lex_word()
+			} else if ch == '"' {
+				self.lex_string()?
 			} else {
-				result.push(match ch {
+				let token: Token = match ch {
 					'$' => Token::Dollar,
 					'.' => Token::Period,
-                    '\'' => self.lex_word(),
+					':' => Token::Colon,
 					'<' => {
 						let mut clone: Lexable = self.clone();
 						let _: Option<char> = clone.next();
@@ -149,6 +382,7 @@ impl LexableExt<'_> for Lexable<'_> {
 								'=' => Token::NotEqual,
 								'<' => Token::GreaterEqual,
 								'>' => Token::LessEqual,
+								'!' => Token::DoubleExclam,
 								_ => return None,
 							});
 
@@ -164,7 +398,18 @@ impl LexableExt<'_> for Lexable<'_> {
 					'≠' => Token::NotEqual,
 					'=' => Token::Equal,
 					'_' => Token::Underscore,
-					'+' => Token::Plus,
+					'~' => Token::Tilde,
+					'+' => {
+						let mut clone: Lexable = self.clone();
+						let _: Option<char> = clone.next();
+
+						if clone.peek() == Some(&'+') {
+							let _: Option<char> = self.next();
+							Token::DoublePlus
+						} else {
+							Token::Plus
+						}
+					}
 					'-' => {
 						let mut clone: Lexable = self.clone();
 						let _: Option<char> = clone.next();
@@ -176,8 +421,43 @@ impl LexableExt<'_> for Lexable<'_> {
 							Token::Minus
 						}
 					}
+					'?' => {
+						let mut clone: Lexable = self.clone();
+						let _: Option<char> = clone.next();
+
+						if clone.peek() == Some(&'?') {
+							let _: Option<char> = self.next();
+							Token::DoubleQuestion
+						} else {
+							Token::Question
+						}
+					}
 					'*' => Token::Asterisk,
-					'/' => Token::Slash,
+					'/' => {
+						let mut clone: Lexable = self.clone();
+						let _: Option<char> = clone.next();
+
+						if clone.peek() == Some(&'/') {
+							let _: Option<char> = self.next();
+							Token::DoubleSlash
+						} else {
+							Token::Slash
+						}
+					}
+					'%' => Token::Percent,
+					'&' => {
+						let mut clone: Lexable = self.clone();
+						let _: Option<char> = clone.next();
+
+						if clone.peek() != Some(&'&') {
+							return Err(LambisError::lex("expected «&&»", Span {end: start.end + 1, ..start}));
+						}
+
+						let _: Option<char> = self.next();
+						Token::DoubleAmpersand
+					}
+					'∧' => Token::DoubleAmpersand,
+					'∨' => Token::DoubleBar,
 					'^' => Token::Circumflex,
 					'[' => Token::OpenBracket,
 					']' => Token::CloseBracket,
@@ -186,18 +466,101 @@ impl LexableExt<'_> for Lexable<'_> {
 					'(' => Token::OpenParen,
 					')' => Token::CloseParen,
 					'λ'|'\\' => Token::Lambda,
-					'|' => Token::Bar,
+					'|' => {
+						let mut clone: Lexable = self.clone();
+						let _: Option<char> = clone.next();
+
+						if clone.peek() == Some(&'|') {
+							let _: Option<char> = self.next();
+							Token::DoubleBar
+						} else {
+							Token::Bar
+						}
+					}
 					'→' => Token::Arrow,
-					 _  => return Err(format!("LexError: what the fuck is {ch}").to_owned()),
-				});
+					 _  => return Err(LambisError::lex(format!("what the fuck is {ch}"), Span {end: start.end + ch.len_utf8(), ..start})),
+				};
 
 				let _: Option<char> = self.next();
-			}
+				token
+			};
+
+			result.push((token, start.to(self.location())));
 		}
 		Ok(result)
 	}
 }
 
-pub fn lex(source: &str) -> Result<Vec<Token>, String> {
-	source.chars().peekable().lex()
+pub fn lex(source: &str) -> Result<Vec<(Token, Span)>, LambisError> {
+	lex_file(source, 0)
+}
+
+pub fn lex_file(source: &str, file: usize) -> Result<Vec<(Token, Span)>, LambisError> {
+	Lexable::new(source, file).lex(&mut vec![])
+}
+
+// the tokens of `source`, with its comments added to `comments`
+pub fn lex_with_comments(source: &str, comments: &mut Vec<Comment>) -> Result<Vec<(Token, Span)>, LambisError> {
+	Lexable::new(source, 0).lex(comments)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{lex, lex_with_comments, Comment, Span, Token};
+
+	fn tokens(source: &str) -> Vec<Token> {
+		lex(source).unwrap().into_iter().map(|(token, _)| token).collect()
+	}
+
+	#[test]
+	fn block_comment_ends_at_the_first_closing_pair() {
+		assert_eq!(tokens("## a ## x"), tokens("x"));
+		assert_eq!(tokens("##a## x"), tokens("x"));
+		assert_eq!(tokens("## ab ##x"), tokens("x"));
+		assert_eq!(tokens("## a\nb\n## x"), tokens("x"));
+	}
+
+	#[test]
+	fn block_comment_keeps_single_markers_inside() {
+		assert_eq!(tokens("## a # b ## x"), tokens("x"));
+		assert_eq!(tokens("## # ## x"), tokens("x"));
+		assert_eq!(tokens("## a ### x"), tokens(""));
+	}
+
+	#[test]
+	fn adjacent_block_comments() {
+		assert_eq!(tokens("#### x"), tokens("x"));
+		assert_eq!(tokens("## a ####b## x"), tokens("x"));
+		assert_eq!(tokens("## a #### b ## x"), tokens("x"));
+		assert_eq!(tokens("## a ## ## b ## x"), tokens("x"));
+		assert_eq!(tokens("## a ## # b\nx"), tokens("x"));
+	}
+
+	#[test]
+	fn unterminated_block_comment_runs_to_the_end() {
+		assert_eq!(tokens("x ## a # b"), tokens("x"));
+	}
+
+	#[test]
+	fn comments_are_collected_with_their_markers() {
+		let source: &str = "x # line\n## a\nb ## y ##c##";
+		let mut comments: Vec<Comment> = vec![];
+		let lexed: Vec<(Token, Span)> = lex_with_comments(source, &mut comments).unwrap();
+
+		assert_eq!(lexed, lex(source).unwrap());
+		assert_eq!(comments.iter().map(|comment: &Comment| comment.text.as_str()).collect::<Vec<&str>>(), ["# line", "## a\nb ##", "##c##"]);
+		for comment in &comments {
+			assert_eq!(&source[comment.span.start..comment.span.end], comment.text);
+		}
+
+		assert_eq!((comments[1].span.line, comments[1].span.column), (2, 1));
+	}
+
+	#[test]
+	fn keywords_are_their_own_tokens() {
+		assert_eq!(tokens("let x = 1 in x"), [Token::Let, Token::Ident("x".to_owned()), Token::Equal, Token::Integer("1".to_owned()), Token::In, Token::Ident("x".to_owned())]);
+		assert_eq!(tokens("import \"a.li\" as a"), [Token::Import, Token::String("a.li".to_owned()), Token::As, Token::Ident("a".to_owned())]);
+		assert_eq!(tokens("when"), [Token::When]);
+		assert_eq!(tokens("inside 'in m::as"), [Token::Ident("inside".to_owned()), Token::Word("in".to_owned()), Token::Ident("m::as".to_owned())]);
+	}
 }
@@ -1,16 +1,159 @@
 pub mod lexer;
+pub mod pattern;
+use std::{cell::RefCell, collections::HashMap, fmt, rc::Rc};
+use crate::interpreter::{bigint::BigInt, builtin::{Builtin, Native}, error::{LambisError, Limit, Span}, rational::Rational, vm::Closure, within};
 
-#[derive(Debug, PartialEq, Clone)]
+// a lambda parameter, lazy ones (`λc~t~f.`) get their argument unevaluated
+// and evaluate it the first time it is referenced
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Parameter {
+	pub name: String,
+	pub lazy: bool,
+}
+
+impl Parameter {
+	pub fn eager(name: &str) -> Self {
+		Self {
+			name: name.to_owned(),
+			lazy: false,
+		}
+	}
+}
+
+impl fmt::Display for Parameter {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		if self.lazy {
+			write!(f, "~")?;
+		}
+
+		write!(f, "{}", self.name)
+	}
+}
+
+// written back the way they can be parsed: `xy` or `(list fn)`
+pub fn parameters(params: &[Parameter]) -> String {
+	if params.iter().all(|param: &Parameter| param.name.chars().count() == 1) {
+		params.iter().map(Parameter::to_string).collect()
+	} else {
+		format!("({})", params.iter().map(Parameter::to_string).collect::<Vec<String>>().join(" "))
+	}
+}
+
+// an argument passed to a lazy parameter, shared by every copy of the scope it is bound in
+#[derive(Debug, PartialEq)]
+pub enum Thunk {
+	// the argument and the variables visible where it was passed
+	Node(ASTNode, HashMap<String, Value>),
+	// the compiled argument inside the caller, run from `ip` until `end` over a copy of its frame
+	Code {
+		closure: Rc<Closure>,
+		locals: Vec<Value>,
+		ip: usize,
+		end: usize
+	},
+	Forced(Value),
+}
+
+#[derive(Debug, Clone)]
 pub enum Value {
 	None,
-	Variable(String),
+	Variable(String, Span),
     Word(String),
+	String(String),
+	Bool(bool),
 	Integer(i128),
+	// only for integers that don't fit in `Integer`
+	BigInt(BigInt),
+	// exact results of divisions, never with a denominator of 1
+	Rational(Rational),
 	Decimal(f64),
+	Array(Vec<Value>),
 	Lambda {
-		args_def: String,
-		content: Box<ASTNode>
+		args_def: Vec<Parameter>,
+		content: Box<ASTNode>,
+		captured: HashMap<String, Value>
 	},
+	Builtin {
+		builtin: Builtin,
+		applied: Vec<Value>
+	},
+	Native {
+		native: Rc<Native>,
+		applied: Vec<Value>
+	},
+	Closure(Rc<Closure>),
+	Thunk(Rc<RefCell<Thunk>>),
+}
+
+// spans only say where the code was written, so values and nodes
+// are equal whenever they do the same, wherever they come from
+impl PartialEq for Value {
+	fn eq(&self, other: &Self) -> bool {
+		match (self, other) {
+			(Value::None, Value::None) => true,
+			(Value::Variable(a, _), Value::Variable(b, _))
+			| (Value::Word(a), Value::Word(b))
+			| (Value::String(a), Value::String(b)) => a == b,
+			(Value::Bool(a), Value::Bool(b)) => a == b,
+			(Value::Integer(a), Value::Integer(b)) => a == b,
+			(Value::BigInt(a), Value::BigInt(b)) => a == b,
+			(Value::Rational(a), Value::Rational(b)) => a == b,
+			(Value::Decimal(a), Value::Decimal(b)) => a == b,
+			(Value::Array(a), Value::Array(b)) => a == b,
+			(
+				Value::Lambda {args_def, content, captured},
+				Value::Lambda {args_def: other_args, content: other_content, captured: other_captured},
+			) => args_def == other_args && content == other_content && captured == other_captured,
+			(Value::Builtin {builtin, applied}, Value::Builtin {builtin: other, applied: other_applied}) => {
+				builtin == other && applied == other_applied
+			}
+			(Value::Native {native, applied}, Value::Native {native: other, applied: other_applied}) => {
+				native == other && applied == other_applied
+			}
+			(Value::Closure(a), Value::Closure(b)) => a == b,
+			(Value::Thunk(a), Value::Thunk(b)) => a == b,
+			_ => false,
+		}
+	}
+}
+
+impl fmt::Display for Value {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			Value::Lambda {args_def, ..} => write!(f, "<λ{}.>", parameters(args_def)),
+			Value::Builtin {builtin, ..} => write!(f, "<builtin {}>", builtin.name()),
+			Value::Native {native, ..} => write!(f, "<native {}>", native.name),
+			Value::Closure(closure) => write!(f, "<λ{}.>", parameters(closure.remaining())),
+			Value::Thunk(thunk) => match &*thunk.borrow() {
+				Thunk::Forced(value) => write!(f, "{value}"),
+				_ => write!(f, "<thunk>"),
+			},
+			Value::Bool(bool) => write!(f, "{bool}"),
+			Value::Integer(int) => write!(f, "{int}"),
+			Value::BigInt(int) => write!(f, "{int}"),
+			Value::Rational(rational) => write!(f, "{rational}"),
+			Value::Decimal(dec) => write!(f, "{dec}"),
+			Value::Word(s) => write!(f, "'{s}"),
+			Value::String(s) => write!(f, "{s}"),
+			Value::None => write!(f, "Nothing"),
+			Value::Variable(name, _) => write!(f, "{name}"),
+			Value::Array(items) => {
+				write!(f, "'(")?;
+				for (index, item) in items.iter().enumerate() {
+					if index > 0 {
+						write!(f, " ")?;
+					}
+
+					if let Value::String(s) = item {
+						write!(f, "{s:?}")?;
+					} else {
+						write!(f, "{item}")?;
+					}
+				}
+				write!(f, ")")
+			}
+		}
+	}
 }
 
 #[derive(Debug, PartialEq, Eq, Clone)]
@@ -19,65 +162,157 @@ pub enum Operation {
 	Substraction,
 	Multiplication,
 	Division,
+	// both round the quotient down, so `x = (x // y) * y + x % y`
+	FloorDivision,
+	Remainder,
 	Exponent,
 
+	Concat,
+	Index,
+	Cons,
+
 	LessEqual,
 	Less,
 	Greater,
 	GreaterEqual,
 	Equal,
 	NotEqual,
+
+	// only evaluate the right side when the left one doesn't decide the result
+	And,
+	Or,
 }
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, Clone)]
 pub enum ASTNode {
 	Nothing,
 	Value(Value),
+	Array(Vec<ASTNode>, Span),
 	Definition {
 		name: String,
-		value: Box<ASTNode>
+		value: Box<ASTNode>,
+		span: Span
+	},
+
+	Let {
+		name: String,
+		value: Box<ASTNode>,
+		body: Box<ASTNode>
 	},
 
 	LambdaCall {
 		lambda: Box<Value>,
-		args: Vec<ASTNode>
+		args: Vec<ASTNode>,
+		span: Span
 	},
 
 	Call {
 		name: String,
-		args: Vec<ASTNode>
+		args: Vec<ASTNode>,
+		span: Span
 	},
 
 	Switch {
 		compared: Box<ASTNode>,
-		cases: Vec<(ASTNode, ASTNode)>
+		cases: Vec<pattern::Case>
 	},
 
-	RationalPart(Box<ASTNode>),
-	IntegerPart(Box<ASTNode>),
-	Print(Box<ASTNode>),
+	RationalPart(Box<ASTNode>, Span),
+	IntegerPart(Box<ASTNode>, Span),
+	Length(Box<ASTNode>, Span),
+	Print(Box<ASTNode>, Span),
+	Import {
+		path: String,
+		namespace: Option<String>,
+		span: Span
+	},
+
+	Input {
+		numeric: bool,
+		span: Span
+	},
 
 	Operation {
 		left: Box<ASTNode>,
 		operation: Operation,
-		right: Box<ASTNode>
+		right: Box<ASTNode>,
+		span: Span
 	},
 }
 
+impl PartialEq for ASTNode {
+	fn eq(&self, other: &Self) -> bool {
+		match (self, other) {
+			(ASTNode::Nothing, ASTNode::Nothing) => true,
+			(ASTNode::Value(a), ASTNode::Value(b)) => a == b,
+			(ASTNode::Array(a, _), ASTNode::Array(b, _)) => a == b,
+			(ASTNode::Definition {name, value, ..}, ASTNode::Definition {name: other, value: other_value, ..}) => {
+				name == other && value == other_value
+			}
+			(
+				ASTNode::Let {name, value, body},
+				ASTNode::Let {name: other, value: other_value, body: other_body},
+			) => name == other && value == other_value && body == other_body,
+			(ASTNode::LambdaCall {lambda, args, ..}, ASTNode::LambdaCall {lambda: other, args: other_args, ..}) => {
+				lambda == other && args == other_args
+			}
+			(ASTNode::Call {name, args, ..}, ASTNode::Call {name: other, args: other_args, ..}) => {
+				name == other && args == other_args
+			}
+			(ASTNode::Switch {compared, cases}, ASTNode::Switch {compared: other, cases: other_cases}) => {
+				compared == other && cases == other_cases
+			}
+			(ASTNode::RationalPart(a, _), ASTNode::RationalPart(b, _))
+			| (ASTNode::IntegerPart(a, _), ASTNode::IntegerPart(b, _))
+			| (ASTNode::Length(a, _), ASTNode::Length(b, _))
+			| (ASTNode::Print(a, _), ASTNode::Print(b, _)) => a == b,
+			(ASTNode::Import {path, namespace, ..}, ASTNode::Import {path: other, namespace: other_namespace, ..}) => {
+				path == other && namespace == other_namespace
+			}
+			(ASTNode::Input {numeric, ..}, ASTNode::Input {numeric: other, ..}) => numeric == other,
+			(
+				ASTNode::Operation {left, operation, right, ..},
+				ASTNode::Operation {left: other_left, operation: other, right: other_right, ..},
+			) => left == other_left && operation == other && right == other_right,
+			_ => false,
+		}
+	}
+}
+
+const KEYWORDS: &str = "`let`, `in`, `import`, `as` and `when` are keywords, which can't be names";
+
 pub struct ParseableIter {
 	tokens: Vec<lexer::Token>,
+	spans: Vec<Span>,
 	current_index: usize,
+	depth: usize,
+	// parsing recurses for every expression nested in another one, operator chains included, and so does
+	// everything walking the syntax tree it makes, so input nested deeper is refused before it can overflow the stack
+	nesting: Option<usize>,
 }
 
 pub type Parseable = ParseableIter;
 
 impl ParseableIter {
-	fn new(source: Vec<lexer::Token>)  -> Self {
+	fn new(source: Vec<(lexer::Token, Span)>, nesting: Option<usize>)  -> Self {
+		let (tokens, spans): (Vec<lexer::Token>, Vec<Span>) = source.into_iter().unzip();
+
 		Self {
-			tokens: source,
+			tokens,
+			spans,
 			current_index: 0,
+			depth: 0,
+			nesting,
 		}
 	}
+
+	fn nested<T>(&mut self, parse: impl FnOnce(&mut Self) -> Result<T, LambisError>) -> Result<T, LambisError> {
+		within(self.depth + 1, self.nesting, Limit::Nesting, self.span())?;
+		self.depth += 1;
+		let result: Result<T, LambisError> = parse(self);
+		self.depth -= 1;
+		result
+	}
 }
 
 // iter impl
@@ -98,58 +333,81 @@ impl Parseable {
 		self.tokens.get(self.current_index).cloned()
 	}
 
+	// only a period written between digits, as in `1.5`, the one of `x = 1.` ends the definition
+	fn is_decimal_point(&self) -> bool {
+		let index: usize = self.current_index;
+		self.peek() == Some(lexer::Token::Period)
+			&& matches!(self.tokens.get(index + 1), Some(lexer::Token::Integer(_)))
+			&& self.spans[index - 1].end == self.spans[index].start
+			&& self.spans[index].end == self.spans[index + 1].start
+	}
+
 	fn is_empty(&self) -> bool {
 		self.peek().is_none()
 	}
+
+	fn span(&self) -> Span {
+		self.spans.get(self.current_index).copied().unwrap_or_else(|| {
+			self.spans.last().map_or_else(Span::default, |last: &Span| Span {
+				start: last.end,
+				column: last.column + (last.end - last.start),
+				..*last
+			})
+		})
+	}
 }
 
 // parser impl
 impl Parseable {
-	fn consume(&mut self, expected: &lexer::Token) -> Result<(), String> {
+	fn consume(&mut self, expected: &lexer::Token) -> Result<(), LambisError> {
+		let span: Span = self.span();
 		let token: lexer::Token = self.next().ok_or_else(||
-			format!("ParsingError: expected «{expected:?}», got end of input")
+			LambisError::parse(format!("expected «{expected}», got end of input"), span)
 		)?;
 
 		if token == *expected {
 			Ok(())
 		} else {
-			Err(format!("ParsingError: expected «{expected:?}», got «{token:?}»"))
+			Err(LambisError::parse(format!("expected «{expected}», got «{token}»"), span))
 		}
 	}
 
-	fn consume_ident(&mut self) -> Result<String, String> {
+	fn consume_ident(&mut self) -> Result<String, LambisError> {
+		let span: Span = self.span();
 		let token: lexer::Token = self.next().ok_or_else(||
-			"ParsingError: expected ident, got end of input".to_owned()
+			LambisError::parse("expected ident, got end of input", span)
 		)?;
 
 		if let lexer::Token::Ident(name) = token {
 			Ok(name)
+		} else if token.is_keyword() {
+			Err(LambisError::parse(format!("expected ident, got the keyword «{token}»"), span).with_hint(KEYWORDS))
 		} else {
-			Err(format!("ParsingError: expected ident, got «{token:?}»"))
+			Err(LambisError::parse(format!("expected ident, got «{token}»"), span))
 		}
 	}
 
-	fn consume_integer(&mut self) -> Result<i128, String> {
+	fn consume_integer(&mut self) -> Result<String, LambisError> {
+		let span: Span = self.span();
 		let token: lexer::Token = self.next().ok_or_else(||
-			"ParsingError: expected number, got end of line".to_owned()
+			LambisError::parse("expected number, got end of input", span)
 		)?;
 
 		if let lexer::Token::Integer(integer) = token {
 			Ok(integer)
 		} else {
-			Err(format!("ParsingError: expected number, got «{token:?}»"))
+			Err(LambisError::parse(format!("expected number, got «{token}»"), span))
 		}
 	}
 
-	fn parse_switch(&mut self, compared: ASTNode) -> Result<ASTNode, String> {
+	fn parse_switch(&mut self, compared: ASTNode) -> Result<ASTNode, LambisError> {
 		let _: Option<lexer::Token> = self.next();
 		self.consume(&lexer::Token::OpenBrace)?;
-		let mut cases: Vec<(ASTNode, ASTNode)> = vec![];
+		let mut cases: Vec<pattern::Case> = vec![];
 
 		while !self.is_empty() && !self.is_delimiter() {
-			let case: ASTNode = self.parse_expression(true, false)?;
-			self.consume(&lexer::Token::Arrow)?;
-			cases.push((case, self.parse_expression(true, true)?));
+			let case: pattern::Case = self.parse_case(&cases)?;
+			cases.push(case);
 		}
 
 		self.consume(&lexer::Token::CloseBrace)?;
@@ -164,7 +422,10 @@ impl Parseable {
 			lexer::Token::CloseParen
 			| lexer::Token::CloseBracket
 			| lexer::Token::CloseBrace
+			| lexer::Token::Bar
 			| lexer::Token::Dollar
+			| lexer::Token::Arrow
+			| lexer::Token::In
 		))
 	}
 
@@ -174,22 +435,44 @@ impl Parseable {
 			| lexer::Token::Minus
 			| lexer::Token::Asterisk
 			| lexer::Token::Slash
+			| lexer::Token::DoubleSlash
+			| lexer::Token::Percent
 			| lexer::Token::Circumflex
+			| lexer::Token::DoublePlus
+			| lexer::Token::DoubleExclam
+			| lexer::Token::Colon
 			| lexer::Token::Equal
 			| lexer::Token::NotEqual
 			| lexer::Token::LessEqual
 			| lexer::Token::Less
 			| lexer::Token::GreaterEqual
 			| lexer::Token::Greater
+			| lexer::Token::DoubleAmpersand
+			| lexer::Token::DoubleBar
 		))
 	}
 
-	fn parse_operation(&mut self, left: ASTNode) -> Result<ASTNode, String> {
+	fn is_logical_operation(&self) -> bool {
+		matches!(self.peek(), Some(lexer::Token::DoubleAmpersand | lexer::Token::DoubleBar))
+	}
+
+	fn parse_operation(&mut self, left: ASTNode) -> Result<ASTNode, LambisError> {
+		let span: Span = self.span();
 		let current: lexer::Token = self.next().unwrap_or_else(|| unreachable!("what"));
 
 		let (allow_operations, allow_repeat): (bool, bool) = match current {
-			lexer::Token::Plus | lexer::Token::Minus => (true, true),
-			lexer::Token::Asterisk | lexer::Token::Slash | lexer::Token::Circumflex => (false, true),
+			lexer::Token::Plus
+			| lexer::Token::Minus
+			| lexer::Token::DoublePlus
+			| lexer::Token::Colon
+			| lexer::Token::DoubleAmpersand
+			| lexer::Token::DoubleBar => (true, true),
+			lexer::Token::Asterisk
+			| lexer::Token::Slash
+			| lexer::Token::DoubleSlash
+			| lexer::Token::Percent
+			| lexer::Token::Circumflex
+			| lexer::Token::DoubleExclam => (false, true),
 			_ => (false, false),
 		};
 
@@ -198,27 +481,37 @@ impl Parseable {
 			lexer::Token::Minus => Operation::Substraction,
 			lexer::Token::Asterisk => Operation::Multiplication,
 			lexer::Token::Slash => Operation::Division,
+			lexer::Token::DoubleSlash => Operation::FloorDivision,
+			lexer::Token::Percent => Operation::Remainder,
 			lexer::Token::Circumflex => Operation::Exponent,
+			lexer::Token::DoublePlus => Operation::Concat,
+			lexer::Token::DoubleExclam => Operation::Index,
+			lexer::Token::Colon => Operation::Cons,
 			lexer::Token::Equal => Operation::Equal,
 			lexer::Token::NotEqual => Operation::NotEqual,
 			lexer::Token::LessEqual => Operation::LessEqual,
 			lexer::Token::Less => Operation::Less,
 			lexer::Token::GreaterEqual => Operation::GreaterEqual,
 			lexer::Token::Greater => Operation::Greater,
+			lexer::Token::DoubleAmpersand => Operation::And,
+			lexer::Token::DoubleBar => Operation::Or,
 			_ => unreachable!("what2"),
 		};
 
 		let index: usize = self.current_index;
 		Ok(match self.parse_expression(true, allow_operations) {
+			Err(error @ LambisError::Limit {..}) => return Err(error),
 			Err(_) => {
 				self.current_index = index;
 				ASTNode::Value(Value::Lambda {
-					args_def: "Y".to_owned(),
+					args_def: vec![Parameter::eager("Y")],
 					content: Box::new(ASTNode::Operation {
 						left: Box::new(left),
 						operation,
-						right: Box::new(ASTNode::Value(Value::Variable("Y".to_owned())))
-					})
+						right: Box::new(ASTNode::Value(Value::Variable("Y".to_owned(), span))),
+						span
+					}),
+					captured: HashMap::new()
 				})
 			}
 
@@ -226,11 +519,13 @@ impl Parseable {
 				let tmp: ASTNode = ASTNode::Operation {
 					left: Box::new(left),
 					operation,
-					right: Box::new(content)
+					right: Box::new(content),
+					span
 				};
 
-				let res: ASTNode = if allow_repeat && self.is_operation() {
-					self.parse_operation(tmp)?
+				// comparisons don't chain, but `x > 0 && x < 10` still has to
+				let res: ASTNode = if (allow_repeat && self.is_operation()) || self.is_logical_operation() {
+					self.nested(|parser: &mut Self| parser.parse_operation(tmp))?
 				} else {
 					tmp
 				};
@@ -244,22 +539,77 @@ impl Parseable {
 		})
 	}
 
-	fn parse_partial_operation(&mut self) -> Result<ASTNode, String> {
-		let result: ASTNode = self.parse_operation(ASTNode::Value(Value::Variable("X".to_owned())))?;
-		let (args_def, content): (String, Box<ASTNode>) =
-			if let ASTNode::Value(Value::Lambda {args_def: ad, content: ct}) = result {
-				("X".to_owned() + ad.as_str(), ct)
+	// every letter is a parameter, unless the names are listed in parentheses
+	// like `λ(list fn).`. `~` makes the parameter after it lazy
+	fn parse_parameters(&mut self) -> Result<Vec<Parameter>, LambisError> {
+		let listed: bool = self.peek() == Some(lexer::Token::OpenParen);
+		if listed {
+			let _: Option<lexer::Token> = self.next();
+		}
+
+		let mut params: Vec<Parameter> = vec![];
+		loop {
+			let lazy: bool = self.peek() == Some(lexer::Token::Tilde);
+			if lazy {
+				let _: Option<lexer::Token> = self.next();
+			}
+
+			let span: Span = self.span();
+			let ident: String = match self.peek() {
+				// `λin.` takes `i` and `n`, keywords are only letters here
+				Some(keyword) if !listed && keyword.is_keyword() => {
+					let _: Option<lexer::Token> = self.next();
+					keyword.to_string()
+				}
+
+				_ => self.consume_ident()?,
+			};
+
+			if listed {
+				if params.iter().any(|param: &Parameter| param.name == ident) {
+					return Err(LambisError::parse(format!("parameter «{ident}» is listed twice"), span));
+				}
+
+				params.push(Parameter {name: ident, lazy});
+			} else {
+				for (index, ch) in ident.chars().enumerate() {
+					params.push(Parameter {
+						name: ch.to_string(),
+						lazy: lazy && index == 0,
+					});
+				}
+			}
+
+			if listed && self.peek() == Some(lexer::Token::CloseParen) {
+				let _: Option<lexer::Token> = self.next();
+				return Ok(params);
+			}
+
+			if !listed && self.peek() == Some(lexer::Token::Period) {
+				return Ok(params);
+			}
+		}
+	}
+
+	fn parse_partial_operation(&mut self) -> Result<ASTNode, LambisError> {
+		let span: Span = self.span();
+		let result: ASTNode = self.parse_operation(ASTNode::Value(Value::Variable("X".to_owned(), span)))?;
+		let (args_def, content): (Vec<Parameter>, Box<ASTNode>) =
+			if let ASTNode::Value(Value::Lambda {args_def: ad, content: ct, ..}) = result {
+				([vec![Parameter::eager("X")], ad].concat(), ct)
 			} else {
-				("X".to_owned(), Box::new(result))
+				(vec![Parameter::eager("X")], Box::new(result))
 			};
 
 		Ok(ASTNode::Value(Value::Lambda {
 			args_def,
-			content
+			content,
+			captured: HashMap::new()
 		}))
 	}
 
-	fn parse_pair(&mut self, from_call: bool, allow_operations: bool) -> Result<ASTNode, String> {
+	fn parse_pair(&mut self, from_call: bool, allow_operations: bool) -> Result<ASTNode, LambisError> {
+		let span: Span = self.span();
 		let _: Option<lexer::Token> = self.next();
         let result: ASTNode;
 
@@ -291,7 +641,8 @@ impl Parseable {
 				} else {
 					ASTNode::LambdaCall {
 						lambda: Box::new(value.clone()),
-						args
+						args,
+						span
 					}
 				}
 			} else {
@@ -317,20 +668,48 @@ impl Parseable {
 		})
 	}
 
-	fn parse_integer_part(&mut self, allow_operations: bool) -> Result<ASTNode, String> {
+	fn parse_array(&mut self, from_call: bool, allow_operations: bool) -> Result<ASTNode, LambisError> {
+		let span: Span = self.span();
+		self.consume(&lexer::Token::Quote)?;
+		self.consume(&lexer::Token::OpenParen)?;
+		let mut items: Vec<ASTNode> = vec![];
+
+		while !self.is_empty() && self.peek() != Some(lexer::Token::CloseParen) {
+			items.push(self.parse_expression(true, true)?);
+		}
+
+		self.consume(&lexer::Token::CloseParen)?;
+		let result: ASTNode = ASTNode::Array(items, span);
+
+		Ok(if allow_operations && self.is_operation() {
+			self.parse_operation(result)?
+		} else if !from_call && self.peek() == Some(lexer::Token::Dollar) {
+			self.parse_switch(result)?
+		} else {
+			if !from_call && self.peek() == Some(lexer::Token::Period) {
+				let _: Option<lexer::Token> = self.next();
+			}
+
+			result
+		})
+	}
+
+	fn parse_integer_part(&mut self, allow_operations: bool) -> Result<ASTNode, LambisError> {
+		let span: Span = self.span();
 		let _: Option<lexer::Token> = self.next();
 
 		Ok(if self.peek() == Some(lexer::Token::CloseBracket) {
 			let _: Option<lexer::Token> =  self.next();
 			ASTNode::Value(Value::Lambda {
-				args_def: "X".to_owned(),
+				args_def: vec![Parameter::eager("X")],
 				content: Box::new(
 					ASTNode::IntegerPart(Box::new(
-						ASTNode::Value(Value::Variable("X".to_owned()))
-					)))
+						ASTNode::Value(Value::Variable("X".to_owned(), span))
+					), span)),
+				captured: HashMap::new()
 			})
 		} else {
-			let result: ASTNode = ASTNode::IntegerPart(Box::new(self.parse_expression(false, true)?));
+			let result: ASTNode = ASTNode::IntegerPart(Box::new(self.parse_expression(false, true)?), span);
 			self.consume(&lexer::Token::CloseBracket)?;
 
 			if allow_operations && self.is_operation() {
@@ -343,20 +722,55 @@ impl Parseable {
 		})
 	}
 
-	fn parse_rational_part(&mut self, allow_operations: bool) -> Result<ASTNode, String> {
+	fn parse_input(&mut self, from_call: bool, allow_operations: bool) -> Result<ASTNode, LambisError> {
+		let span: Span = self.span();
+		let numeric: bool = self.next() == Some(lexer::Token::DoubleQuestion);
+		let result: ASTNode = ASTNode::Input {numeric, span};
+
+		Ok(if allow_operations && self.is_operation() {
+			self.parse_operation(result)?
+		} else if !from_call && self.peek() == Some(lexer::Token::Dollar) {
+			self.parse_switch(result)?
+		} else {
+			if !from_call && self.peek() == Some(lexer::Token::Period) {
+				let _: Option<lexer::Token> = self.next();
+			}
+
+			result
+		})
+	}
+
+	fn parse_length(&mut self, allow_operations: bool) -> Result<ASTNode, LambisError> {
+		let span: Span = self.span();
+		let _: Option<lexer::Token> = self.next();
+		let result: ASTNode = ASTNode::Length(Box::new(self.parse_expression(false, true)?), span);
+		self.consume(&lexer::Token::Bar)?;
+
+		Ok(if allow_operations && self.is_operation() {
+			self.parse_operation(result)?
+		} else if self.peek() == Some(lexer::Token::Dollar) {
+			self.parse_switch(result)?
+		} else {
+			result
+		})
+	}
+
+	fn parse_rational_part(&mut self, allow_operations: bool) -> Result<ASTNode, LambisError> {
+		let span: Span = self.span();
 		let _: Option<lexer::Token> = self.next();
 		Ok(if self.peek() == Some(lexer::Token::CloseBrace) {
 			let _: Option<lexer::Token> = self.next();
 
 			ASTNode::Value(Value::Lambda {
-				args_def: "X".to_owned(),
+				args_def: vec![Parameter::eager("X")],
 				content: Box::new(
 				ASTNode::RationalPart(Box::new(
-					ASTNode::Value(Value::Variable("X".to_owned()))
-				)))
+					ASTNode::Value(Value::Variable("X".to_owned(), span))
+				), span)),
+				captured: HashMap::new()
 			})
 		} else {
-			let result: ASTNode = ASTNode::RationalPart(Box::new(self.parse_expression(false, true)?));
+			let result: ASTNode = ASTNode::RationalPart(Box::new(self.parse_expression(false, true)?), span);
 			self.consume(&lexer::Token::CloseBrace)?;
 
 			if allow_operations && self.is_operation() {
@@ -368,11 +782,45 @@ impl Parseable {
 	}
 
 
-	fn parse_ident(&mut self, from_call: bool, allow_operations: bool) -> Result<ASTNode, String> {
+	fn parse_let(&mut self) -> Result<ASTNode, LambisError> {
+		let name: String = self.consume_ident()?;
+		self.consume(&lexer::Token::Equal)?;
+		let value: ASTNode = self.parse_expression(false, true)?;
+		self.consume(&lexer::Token::In)?;
+
+		Ok(ASTNode::Let {
+			name,
+			value: Box::new(value),
+			body: Box::new(self.parse_expression(false, true)?)
+		})
+	}
+
+	fn parse_import(&mut self, span: Span) -> Result<ASTNode, LambisError> {
+		let path_span: Span = self.span();
+		let Some(lexer::Token::String(path)) = self.next() else {
+			return Err(LambisError::parse("expected path string after «import»", path_span));
+		};
+
+		let namespace: Option<String> = if self.peek() == Some(lexer::Token::As) {
+			let _: Option<lexer::Token> = self.next();
+			Some(self.consume_ident()?)
+		} else {
+			None
+		};
+
+		if self.peek() == Some(lexer::Token::Period) {
+			let _: Option<lexer::Token> = self.next();
+		}
+
+		Ok(ASTNode::Import {path, namespace, span})
+	}
+
+	fn parse_ident(&mut self, from_call: bool, allow_operations: bool) -> Result<ASTNode, LambisError> {
+		let span: Span = self.span();
 		let name: String = self.consume_ident()?;
 
 		Ok(if from_call {
-			let result: ASTNode = ASTNode::Value(Value::Variable(name));
+			let result: ASTNode = ASTNode::Value(Value::Variable(name, span));
 			if allow_operations && self.is_operation() {
 				self.parse_operation(result)?
 			} else if self.peek() == Some(lexer::Token::Dollar) {
@@ -384,7 +832,8 @@ impl Parseable {
 			let _: Option<lexer::Token> = self.next();
 			ASTNode::Definition {
 				name,
-				value: Box::new(self.parse_expression(false, true)?)
+				value: Box::new(self.parse_expression(false, true)?),
+				span
 			}
 		} else {
 			let mut args: Vec<ASTNode> = vec![];
@@ -397,11 +846,12 @@ impl Parseable {
 			}
 
 			let result: ASTNode = if args.is_empty() {
-				ASTNode::Value(Value::Variable(name))
+				ASTNode::Value(Value::Variable(name, span))
 			} else {
 				ASTNode::Call {
 					name,
-					args
+					args,
+					span
 				}
 			};
 
@@ -426,37 +876,45 @@ impl Parseable {
 		})
 	}
 
-	fn parse_expression(&mut self, from_call: bool, allow_operations: bool) -> Result<ASTNode, String> {
+	fn parse_expression(&mut self, from_call: bool, allow_operations: bool) -> Result<ASTNode, LambisError> {
+		self.nested(|parser: &mut Self| parser.parse_nested_expression(from_call, allow_operations))
+	}
+
+	fn parse_nested_expression(&mut self, from_call: bool, allow_operations: bool) -> Result<ASTNode, LambisError> {
 		let Some(current): Option<lexer::Token> = self.peek() else {
 			return Ok(ASTNode::Nothing)
 		};
 
 		match current {
 			lexer::Token::Exclam => {
+				let span: Span = self.span();
 				let _: Option<lexer::Token> = self.next();
 				Ok(if from_call {
                     ASTNode::Value(Value::Lambda {
-                        args_def: "X".to_owned(),
+                        args_def: vec![Parameter::eager("X")],
                         content: Box::new(ASTNode::Print(Box::new(
-                                    ASTNode::Value(Value::Variable("X".to_owned()))))),
+                                    ASTNode::Value(Value::Variable("X".to_owned(), span))), span)),
+                        captured: HashMap::new(),
                     })
                 } else {
-                    ASTNode::Print(Box::new(self.parse_expression(false, true)?))
+                    ASTNode::Print(Box::new(self.parse_expression(false, true)?), span)
                 })
 			}
 
+			lexer::Token::Question | lexer::Token::DoubleQuestion => self.parse_input(from_call, allow_operations),
 			lexer::Token::OpenBracket => self.parse_integer_part(allow_operations),
 			lexer::Token::OpenBrace => self.parse_rational_part(allow_operations),
 			lexer::Token::OpenParen => self.parse_pair(from_call, allow_operations),
+			lexer::Token::Bar => self.parse_length(allow_operations),
 
 			lexer::Token::Lambda => {
 				let _: Option<lexer::Token> = self.next();
-				let mut args_def: String = self.consume_ident()?;
+				let mut args_def: Vec<Parameter> = self.parse_parameters()?;
 				self.consume(&lexer::Token::Period)?;
 				let mut body: Box<ASTNode> = Box::new(self.parse_expression(false, true)?);
 
-				if let ASTNode::Value(Value::Lambda{args_def: args_def_, content}) = *body.clone() {
-					args_def += args_def_.as_str();
+				if let ASTNode::Value(Value::Lambda{args_def: args_def_, content, ..}) = *body.clone() {
+					args_def.extend(args_def_);
 					body = content;
 				}
 
@@ -466,24 +924,58 @@ impl Parseable {
 
 				Ok(ASTNode::Value(Value::Lambda{
 					args_def,
-					content: body
+					content: body,
+					captured: HashMap::new()
 				}))
 			}
 
             lexer::Token::Word(_) => {
                 let lexer::Token::Word(s): lexer::Token = self.next().unwrap() else {unreachable!("urmom")};
-                Ok(ASTNode::Value(Value::Word(s)))
+                let result: ASTNode = ASTNode::Value(Value::Word(s));
+
+                Ok(if allow_operations && self.is_operation() {
+                    self.parse_operation(result)?
+                } else if !from_call && self.peek() == Some(lexer::Token::Dollar) {
+                    self.parse_switch(result)?
+                } else {
+                    if !from_call && self.peek() == Some(lexer::Token::Period) {
+                        let _: Option<lexer::Token> = self.next();
+                    }
+
+                    result
+                })
             }
 
+			lexer::Token::Quote => self.parse_array(from_call, allow_operations),
+
+			lexer::Token::String(_) => {
+				let Some(lexer::Token::String(s)): Option<lexer::Token> = self.next() else {unreachable!("urmom")};
+				let result: ASTNode = ASTNode::Value(Value::String(s));
+
+				Ok(if allow_operations && self.is_operation() {
+					self.parse_operation(result)?
+				} else if !from_call && self.peek() == Some(lexer::Token::Dollar) {
+					self.parse_switch(result)?
+				} else {
+					if !from_call && self.peek() == Some(lexer::Token::Period) {
+						let _: Option<lexer::Token> = self.next();
+					}
+
+					result
+				})
+			}
+
 			lexer::Token::Integer(_) => {
-				let integer: i128 = self.consume_integer()?;
+				let integer: String = self.consume_integer()?;
 
-				let result: ASTNode = if self.peek() == Some(lexer::Token::Period) {
+				let result: ASTNode = if self.is_decimal_point() {
 					let _: Option<lexer::Token> = self.next();
-					let rational: i128 = self.consume_integer()?;
+					let rational: String = self.consume_integer()?;
 					ASTNode::Value(Value::Decimal(format!("{integer}.{rational}").parse::<f64>().unwrap()))
-				} else {
+				} else if let Ok(integer) = integer.parse::<i128>() {
 					ASTNode::Value(Value::Integer(integer))
+				} else {
+					ASTNode::Value(Value::BigInt(BigInt::parse(integer.as_str()).unwrap()))
 				};
 
 				Ok(if allow_operations && self.is_operation() {
@@ -496,26 +988,154 @@ impl Parseable {
 			}
 
 			lexer::Token::Ident(_) => self.parse_ident(from_call, allow_operations),
+			lexer::Token::Let => {
+				let _: Option<lexer::Token> = self.next();
+				self.parse_let()
+			}
+
+			lexer::Token::Import => {
+				let span: Span = self.span();
+				let _: Option<lexer::Token> = self.next();
+				self.parse_import(span)
+			}
+
 			_ if self.is_operation() => self.parse_partial_operation(),
 
 			what => {
-				Err(format!("ParsingError: expected expression start, got «{:?}», previous token is {:?}",
-						what, self.tokens.get(self.current_index-1)))
+				let error: LambisError = LambisError::parse(format!("expected expression start, got «{what}»"), self.span());
+				Err(if what.is_keyword() { error.with_hint(KEYWORDS) } else { error })
 			}
 		}
 	}
 
-	pub fn parse(&mut self) -> Result<Vec<ASTNode>, String> {
+	// any statement can end with a period, not only the ones that would take what follows as arguments
+	fn parse_statement(&mut self) -> Result<ASTNode, LambisError> {
+		let node: ASTNode = self.parse_expression(false, true)?;
+		if self.peek() == Some(lexer::Token::Period) {
+			let _: Option<lexer::Token> = self.next();
+		}
+
+		Ok(node)
+	}
+
+	pub fn parse(&mut self) -> Result<Vec<ASTNode>, LambisError> {
 		let mut result: Vec<ASTNode> = vec![];
 
 		while !self.is_empty() {
-			result.push(self.parse_expression(false, true)?);
+			result.push(self.parse_statement()?);
 		}
 
 		Ok(result)
 	}
 }
 
-pub fn parse(source: &str) -> Result<Vec<ASTNode>, String> {
-	Parseable::new(lexer::lex(source)?).parse()
+pub fn parse(source: &str) -> Result<Vec<ASTNode>, LambisError> {
+	parse_file(source, 0, None)
+}
+
+// refuses expressions nested deeper than `nesting`, when there is a limit
+pub fn parse_file(source: &str, file: usize, nesting: Option<usize>) -> Result<Vec<ASTNode>, LambisError> {
+	Parseable::new(lexer::lex_file(source, file)?, nesting).parse()
+}
+
+// every top-level statement together with the span of all its tokens
+pub fn parse_statements(tokens: Vec<(lexer::Token, Span)>) -> Result<Vec<(ASTNode, Span)>, LambisError> {
+	let mut parseable: Parseable = Parseable::new(tokens, None);
+	let mut result: Vec<(ASTNode, Span)> = vec![];
+
+	while !parseable.is_empty() {
+		let start: Span = parseable.span();
+		let node: ASTNode = parseable.parse_statement()?;
+		result.push((node, start.to(parseable.spans[parseable.current_index - 1])));
+	}
+
+	Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{lexer, parameters, parse, parse_statements, ASTNode, LambisError, Parameter, Span, Value};
+
+	#[test]
+	fn statements_span_all_their_tokens() {
+		let source: &str = "x = \"a\".\nf = λn. n ${\n\t0 -> 1\n\t_ -> 2\n}.\n\n! (f x)\n";
+		let statements: Vec<(ASTNode, Span)> = parse_statements(lexer::lex(source).unwrap()).unwrap();
+
+		let texts: Vec<&str> = statements.iter().map(|(_, span): &(ASTNode, Span)| &source[span.start..span.end]).collect();
+		assert_eq!(texts, ["x = \"a\".", "f = λn. n ${\n\t0 -> 1\n\t_ -> 2\n}.", "! (f x)"]);
+		assert_eq!(statements.iter().map(|(_, span): &(ASTNode, Span)| span.line).collect::<Vec<usize>>(), [1, 2, 7]);
+		assert_eq!(statements.into_iter().map(|(node, _): (ASTNode, Span)| node).collect::<Vec<ASTNode>>(), parse(source).unwrap());
+	}
+
+	#[test]
+	fn errors_show_tokens_as_they_are_written() {
+		let messages: Vec<String> = ["! )", "x = 1 ${ 1 -> ) }", "! ['a", "λ1. 1"]
+			.into_iter()
+			.map(|source: &str| parse(source).unwrap_err().message().to_owned())
+			.collect();
+
+		assert_eq!(messages, [
+			"expected expression start, got «)»",
+			"expected expression start, got «)»",
+			"expected «]», got end of input",
+			"expected ident, got «1»",
+		]);
+
+		let error: LambisError = parse("! (1 2 3").unwrap_err();
+		assert_eq!(error.message(), "expected «)», got «2»");
+		assert_eq!((error.span().line, error.span().column), (1, 6));
+	}
+
+	#[test]
+	fn nodes_are_equal_wherever_they_are_written() {
+		let source: Vec<ASTNode> = parse("f = λx. x ${\n\t0 when x = 0 -> (g x)\n\t_ -> [x + y]\n}.").unwrap();
+		let moved: Vec<ASTNode> = parse("\n\n  f=λx.x${0 when x=0->(g x) _->[x+y]}.").unwrap();
+		assert_eq!(source, moved);
+		assert_ne!(source, parse("f = λx. x ${\n\t0 when x = 0 -> (g x)\n\t_ -> [x + z]\n}.").unwrap());
+	}
+
+	#[test]
+	fn statements_end_with_a_period_unless_it_is_between_digits() {
+		let value = |value: Value| ASTNode::Definition {name: "x".to_owned(), value: Box::new(ASTNode::Value(value)), span: Span::default()};
+		assert_eq!(parse("x = 5.\nx = 1.5\nx = |'(1)|.\n(f x).").unwrap(), parse("x = 5\nx = 1.5\nx = |'(1)|\n(f x)").unwrap());
+		assert_eq!(parse("x = 5.").unwrap(), [value(Value::Integer(5))]);
+		assert_eq!(parse("x = 1.5").unwrap(), [value(Value::Decimal(1.5))]);
+		assert_eq!(parse("x = 1. 5").unwrap(), [value(Value::Integer(1)), ASTNode::Value(Value::Integer(5))]);
+	}
+
+	fn params(source: &str) -> Vec<Parameter> {
+		match parse(source).unwrap().pop() {
+			Some(ASTNode::Value(Value::Lambda {args_def, ..})) => args_def,
+			what => panic!("expected a lambda, got {what:?}"),
+		}
+	}
+
+	#[test]
+	fn parameters_can_be_letters_or_names() {
+		let lazy = |name: &str| Parameter {name: name.to_owned(), lazy: true};
+		assert_eq!(params("λxy. x"), [Parameter::eager("x"), Parameter::eager("y")]);
+		assert_eq!(params("λ(list fn). list"), [Parameter::eager("list"), Parameter::eager("fn")]);
+		assert_eq!(params("λ(cond ~then ~else). cond"), [Parameter::eager("cond"), lazy("then"), lazy("else")]);
+		assert_eq!(params("λc~t~f. c"), [Parameter::eager("c"), lazy("t"), lazy("f")]);
+
+		assert_eq!(parameters(&params("λ(x y). x")), "xy");
+		assert_eq!(parameters(&params("λ(cond ~then). cond")), "(cond ~then)");
+		assert_eq!(parse("λ(a b a). a").unwrap_err().message(), "parameter «a» is listed twice");
+	}
+
+	#[test]
+	fn keywords_are_not_names() {
+		for (source, message) in [
+			("in = 1.", "expected expression start, got «in»"),
+			("let when = 1 in 2", "expected ident, got the keyword «when»"),
+			("λ(as x). x", "expected ident, got the keyword «as»"),
+			("import \"a.li\" as let.", "expected ident, got the keyword «let»"),
+		] {
+			let error: LambisError = parse(source).unwrap_err();
+			assert_eq!(error.message(), message);
+			assert_eq!(error.hint(), Some("`let`, `in`, `import`, `as` and `when` are keywords, which can't be names"));
+		}
+
+		assert_eq!(params("λin. i"), [Parameter::eager("i"), Parameter::eager("n")]);
+	}
 }
@@ -0,0 +1,282 @@
+use std::fmt;
+
+use super::{lexer::Token, ASTNode, Parseable, Value};
+use crate::interpreter::{bigint::BigInt, error::{LambisError, Span}};
+
+// what a switch case compares the switch subject against
+#[derive(Debug, PartialEq, Clone)]
+pub enum Pattern {
+	// `_`, matches anything without binding it
+	Wildcard,
+	// `x`, matches anything and binds it to `x` in the guard and the action
+	Binding(String),
+	// numbers, strings, words, `true`, `false` and `()`
+	Literal(Value),
+	// `1..10`, inclusive on both ends
+	Range(Value, Value),
+	// `=y` or `=(f 1)`, matches what the expression evaluates to in the scope of the switch
+	Pinned(Box<ASTNode>),
+	// `(h . t)`, a non-empty array split into its first item and the rest
+	Cons(Box<Pattern>, Box<Pattern>),
+	// `'(a b)`, an array of exactly that many items
+	Array(Vec<Pattern>),
+}
+
+#[derive(Debug, Clone)]
+pub struct Case {
+	pub pattern: Pattern,
+	// `when guard`, the case only matches when it evaluates to true
+	pub guard: Option<ASTNode>,
+	pub action: ASTNode,
+	pub span: Span,
+}
+
+// like nodes, cases are equal wherever they were written
+impl PartialEq for Case {
+	fn eq(&self, other: &Self) -> bool {
+		self.pattern == other.pattern && self.guard == other.guard && self.action == other.action
+	}
+}
+
+impl Pattern {
+	// the names the pattern binds, in the order the interpreter binds them
+	pub fn names(&self) -> Vec<String> {
+		let mut names: Vec<String> = vec![];
+		self.collect_names(&mut names);
+		names
+	}
+
+	// the pinned expressions, in the order the interpreter matches them
+	pub fn pins(&self) -> Vec<&ASTNode> {
+		match self {
+			Pattern::Pinned(pin) => vec![pin],
+			Pattern::Cons(head, tail) => [head.pins(), tail.pins()].concat(),
+			Pattern::Array(items) => items.iter().flat_map(Pattern::pins).collect(),
+			Pattern::Wildcard | Pattern::Binding(_) | Pattern::Literal(_) | Pattern::Range(..) => vec![],
+		}
+	}
+
+	pub fn map_pins(self, f: &mut impl FnMut(ASTNode) -> ASTNode) -> Pattern {
+		match self {
+			Pattern::Pinned(pin) => Pattern::Pinned(Box::new(f(*pin))),
+			Pattern::Cons(head, tail) => Pattern::Cons(Box::new(head.map_pins(f)), Box::new(tail.map_pins(f))),
+			Pattern::Array(items) => Pattern::Array(items.into_iter().map(|item: Pattern| item.map_pins(f)).collect()),
+			other => other,
+		}
+	}
+
+	fn collect_names(&self, names: &mut Vec<String>) {
+		match self {
+			Pattern::Binding(name) => names.push(name.clone()),
+			Pattern::Cons(head, tail) => {
+				head.collect_names(names);
+				tail.collect_names(names);
+			}
+			Pattern::Array(items) => items.iter().for_each(|item: &Pattern| item.collect_names(names)),
+			Pattern::Wildcard | Pattern::Literal(_) | Pattern::Range(..) | Pattern::Pinned(_) => {}
+		}
+	}
+
+	// whether every value matching `other` also matches this pattern,
+	// which nothing but bindings can tell for a pin
+	fn covers(&self, other: &Pattern) -> bool {
+		match (self, other) {
+			(Pattern::Wildcard | Pattern::Binding(_), _) => true,
+			(Pattern::Literal(a), Pattern::Literal(b)) => a == b,
+			(Pattern::Range(a, b), Pattern::Range(c, d)) => a == c && b == d,
+			(Pattern::Cons(a, b), Pattern::Cons(c, d)) => a.covers(c) && b.covers(d),
+			(Pattern::Array(a), Pattern::Array(b)) => {
+				a.len() == b.len() && a.iter().zip(b).all(|(a, b): (&Pattern, &Pattern)| a.covers(b))
+			}
+			_ => false,
+		}
+	}
+}
+
+impl fmt::Display for Pattern {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			Pattern::Wildcard => write!(f, "_"),
+			Pattern::Binding(name) => write!(f, "{name}"),
+			Pattern::Literal(Value::None) => write!(f, "()"),
+			Pattern::Literal(Value::String(s)) => write!(f, "{s:?}"),
+			Pattern::Literal(value) => write!(f, "{value}"),
+			Pattern::Range(from, to) => write!(f, "{from}..{to}"),
+			// only error messages show patterns, which don't need the whole expression
+			Pattern::Pinned(pin) => match &**pin {
+				ASTNode::Value(Value::Variable(name, _)) => write!(f, "={name}"),
+				_ => write!(f, "=(…)"),
+			},
+			Pattern::Cons(head, tail) => write!(f, "({head} . {tail})"),
+			Pattern::Array(items) => {
+				write!(f, "'(")?;
+				for (index, item) in items.iter().enumerate() {
+					if index > 0 {
+						write!(f, " ")?;
+					}
+
+					write!(f, "{item}")?;
+				}
+				write!(f, ")")
+			}
+		}
+	}
+}
+
+impl Parseable {
+	// `pattern -> action` or `pattern when guard -> action`
+	pub(super) fn parse_case(&mut self, previous: &[Case]) -> Result<Case, LambisError> {
+		let span: Span = self.span();
+		let pattern: Pattern = self.parse_pattern()?;
+
+		let mut names: Vec<String> = pattern.names();
+		names.sort();
+		if let Some(name) = names.windows(2).find(|pair: &&[String]| pair[0] == pair[1]).map(|pair: &[String]| &pair[0]) {
+			return Err(LambisError::parse(format!("«{name}» is bound more than once in «{pattern}»"), span));
+		}
+
+		if let Some(earlier) = previous.iter().find(|case: &&Case| case.guard.is_none() && case.pattern.covers(&pattern)) {
+			return Err(LambisError::parse(format!("unreachable case «{pattern}»"), span)
+				.with_hint(format!("every value it matches is already matched by «{}»", earlier.pattern)));
+		}
+
+		// guards and actions are parsed like call arguments, a call in them needs parentheses
+		let guard: Option<ASTNode> = if self.peek() == Some(Token::When) {
+			let _: Option<Token> = self.next();
+			let guard: ASTNode = self.parse_expression(true, true)?;
+			self.consume(&Token::Arrow).map_err(|error: LambisError| error.with_hint("a call in a guard needs parentheses, as in `when (f x)`"))?;
+			Some(guard)
+		} else {
+			self.consume(&Token::Arrow)?;
+			None
+		};
+
+		// the action ends where the next case starts, so that one starting
+		// with `-` or `=` isn't taken as an operation on the action
+		let rest: Vec<Token> = self.tokens.split_off(self.action_end());
+		let action: Result<ASTNode, LambisError> = self.parse_expression(true, true);
+		self.tokens.extend(rest);
+
+		Ok(Case {
+			pattern,
+			guard,
+			action: action?,
+			span,
+		})
+	}
+
+	// the first `-` or `=` starting a line after the action, or the end of the switch
+	fn action_end(&self) -> usize {
+		let mut depth: usize = 0;
+		for index in self.current_index + 1..self.tokens.len() {
+			match self.tokens[index] {
+				Token::OpenParen | Token::OpenBrace | Token::OpenBracket => depth += 1,
+				Token::CloseParen | Token::CloseBrace | Token::CloseBracket if depth == 0 => break,
+				Token::CloseParen | Token::CloseBrace | Token::CloseBracket => depth -= 1,
+				Token::Minus | Token::Equal if depth == 0 && self.spans[index].line > self.spans[index - 1].line => return index,
+				_ => {}
+			}
+		}
+
+		self.tokens.len()
+	}
+
+	fn parse_pattern(&mut self) -> Result<Pattern, LambisError> {
+		let span: Span = self.span();
+
+		match self.peek() {
+			Some(Token::Underscore) => {
+				let _: Option<Token> = self.next();
+				Ok(Pattern::Wildcard)
+			}
+
+			Some(Token::Ident(name)) => {
+				let _: Option<Token> = self.next();
+				Ok(match name.as_str() {
+					"true" => Pattern::Literal(Value::Bool(true)),
+					"false" => Pattern::Literal(Value::Bool(false)),
+					_ if name.contains("::") => return Err(LambisError::parse(format!("cannot bind the qualified name «{name}»"), span)),
+					_ => Pattern::Binding(name),
+				})
+			}
+
+			Some(Token::OpenParen) => {
+				let _: Option<Token> = self.next();
+				if self.peek() == Some(Token::CloseParen) {
+					let _: Option<Token> = self.next();
+					return Ok(Pattern::Literal(Value::None));
+				}
+
+				let head: Pattern = self.nested(Self::parse_pattern)?;
+				self.consume(&Token::Period)?;
+				let tail: Pattern = self.nested(Self::parse_pattern)?;
+				self.consume(&Token::CloseParen)?;
+				Ok(Pattern::Cons(Box::new(head), Box::new(tail)))
+			}
+
+			Some(Token::Quote) => {
+				let _: Option<Token> = self.next();
+				self.consume(&Token::OpenParen)?;
+
+				let mut items: Vec<Pattern> = vec![];
+				while !self.is_empty() && self.peek() != Some(Token::CloseParen) {
+					items.push(self.nested(Self::parse_pattern)?);
+				}
+
+				self.consume(&Token::CloseParen)?;
+				Ok(Pattern::Array(items))
+			}
+
+			Some(Token::Word(word)) => {
+				let _: Option<Token> = self.next();
+				Ok(Pattern::Literal(Value::Word(word)))
+			}
+
+			Some(Token::Equal) => {
+				let _: Option<Token> = self.next();
+				Ok(Pattern::Pinned(Box::new(self.parse_expression(true, true)?)))
+			}
+
+			Some(Token::String(_) | Token::Integer(_) | Token::Minus) => {
+				let from: Value = self.parse_literal()?;
+				if self.peek() != Some(Token::Period) || self.tokens.get(self.current_index + 1) != Some(&Token::Period) {
+					return Ok(Pattern::Literal(from));
+				}
+
+				self.consume(&Token::Period)?;
+				self.consume(&Token::Period)?;
+				Ok(Pattern::Range(from, self.parse_literal()?))
+			}
+
+			Some(what) => Err(LambisError::parse(format!("expected a pattern, got «{what}»"), span)),
+			None => Err(LambisError::parse("expected a pattern, got end of input", span)),
+		}
+	}
+
+	// a string or a possibly negative number, which can't end with a period, so `1..10` is a range
+	fn parse_literal(&mut self) -> Result<Value, LambisError> {
+		if let Some(Token::String(s)) = self.peek() {
+			let _: Option<Token> = self.next();
+			return Ok(Value::String(s));
+		}
+
+		let sign: &str = if self.peek() == Some(Token::Minus) {
+			let _: Option<Token> = self.next();
+			"-"
+		} else {
+			""
+		};
+
+		let integer: String = format!("{sign}{}", self.consume_integer()?);
+
+		Ok(if self.peek() == Some(Token::Period) && matches!(self.tokens.get(self.current_index + 1), Some(Token::Integer(_))) {
+			let _: Option<Token> = self.next();
+			let rational: String = self.consume_integer()?;
+			Value::Decimal(format!("{integer}.{rational}").parse::<f64>().unwrap())
+		} else if let Ok(integer) = integer.parse::<i128>() {
+			Value::Integer(integer)
+		} else {
+			Value::BigInt(BigInt::parse(integer.as_str()).unwrap())
+		})
+	}
+}
@@ -0,0 +1,189 @@
+use std::{
+	cmp::Ordering,
+	fmt,
+	ops::{Add, Mul, Neg, Sub},
+};
+
+use super::bigint::BigInt;
+
+// always reduced with a positive denominator, so derived equality is numeric equality
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Rational {
+	numerator: BigInt,
+	denominator: BigInt,
+}
+
+impl Rational {
+	// `None` when the denominator is zero
+	pub fn new(numerator: BigInt, denominator: BigInt) -> Option<Self> {
+		if denominator.is_zero() {
+			return None;
+		}
+
+		let divisor: BigInt = numerator.gcd(&denominator);
+		let divisor: BigInt = if denominator.is_negative() { -&divisor } else { divisor };
+		let (numerator, _): (BigInt, BigInt) = numerator.div_rem(&divisor)?;
+		let (denominator, _): (BigInt, BigInt) = denominator.div_rem(&divisor)?;
+
+		Some(Self {numerator, denominator})
+	}
+
+	pub fn numerator(&self) -> &BigInt {
+		&self.numerator
+	}
+
+	pub fn denominator(&self) -> &BigInt {
+		&self.denominator
+	}
+
+	pub fn is_integer(&self) -> bool {
+		self.denominator == BigInt::from(1)
+	}
+
+	// the greatest integer not above the number
+	pub fn floor(&self) -> BigInt {
+		let (quotient, remainder): (BigInt, BigInt) = self.numerator
+			.div_rem(&self.denominator)
+			.expect("rationals never have a zero denominator");
+
+		if remainder.is_negative() {
+			&quotient - &BigInt::from(1)
+		} else {
+			quotient
+		}
+	}
+
+	pub fn to_f64(&self) -> f64 {
+		// keeps about 20 significant digits in the quotient, so huge terms don't overflow to infinity
+		let shift: usize = (20 + self.denominator.digits()).saturating_sub(self.numerator.digits());
+		let (quotient, _): (BigInt, BigInt) = (&self.numerator * &BigInt::ten_to(shift))
+			.div_rem(&self.denominator)
+			.expect("rationals never have a zero denominator");
+
+		format!("{quotient}e-{shift}").parse::<f64>().unwrap_or(f64::NAN)
+	}
+
+	pub fn pow(&self, exponent: u32) -> Self {
+		Self {
+			numerator: self.numerator.pow(exponent),
+			denominator: self.denominator.pow(exponent),
+		}
+	}
+
+	// `None` when dividing by zero
+	pub fn checked_div(&self, other: &Self) -> Option<Self> {
+		Self::new(&self.numerator * &other.denominator, &self.denominator * &other.numerator)
+	}
+}
+
+impl From<BigInt> for Rational {
+	fn from(numerator: BigInt) -> Self {
+		Self {
+			numerator,
+			denominator: BigInt::from(1),
+		}
+	}
+}
+
+impl Neg for &Rational {
+	type Output = Rational;
+
+	fn neg(self) -> Rational {
+		Rational {
+			numerator: -&self.numerator,
+			denominator: self.denominator.clone(),
+		}
+	}
+}
+
+impl Add for &Rational {
+	type Output = Rational;
+
+	fn add(self, other: &Rational) -> Rational {
+		Rational::new(
+			&(&self.numerator * &other.denominator) + &(&other.numerator * &self.denominator),
+			&self.denominator * &other.denominator,
+		).expect("rationals never have a zero denominator")
+	}
+}
+
+impl Sub for &Rational {
+	type Output = Rational;
+
+	fn sub(self, other: &Rational) -> Rational {
+		self + &-other
+	}
+}
+
+impl Mul for &Rational {
+	type Output = Rational;
+
+	fn mul(self, other: &Rational) -> Rational {
+		Rational::new(&self.numerator * &other.numerator, &self.denominator * &other.denominator)
+			.expect("rationals never have a zero denominator")
+	}
+}
+
+impl Ord for Rational {
+	fn cmp(&self, other: &Self) -> Ordering {
+		(&self.numerator * &other.denominator).cmp(&(&other.numerator * &self.denominator))
+	}
+}
+
+impl PartialOrd for Rational {
+	fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+		Some(self.cmp(other))
+	}
+}
+
+impl fmt::Display for Rational {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "{}/{}", self.numerator, self.denominator)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{BigInt, Rational};
+
+	fn ratio(numerator: i128, denominator: i128) -> Rational {
+		Rational::new(BigInt::from(numerator), BigInt::from(denominator)).unwrap()
+	}
+
+	#[test]
+	fn rationals_are_reduced_with_a_positive_denominator() {
+		for (numerator, denominator, reduced) in [(6, 4, "3/2"), (6, -4, "-3/2"), (-6, -4, "3/2"), (0, -5, "0/1"), (7, 7, "1/1")] {
+			assert_eq!(ratio(numerator, denominator).to_string(), reduced);
+		}
+
+		assert_eq!(ratio(2, 4), ratio(-1, -2));
+		assert!(ratio(-8, 4).is_integer());
+		assert!(!ratio(1, 3).is_integer());
+	}
+
+	#[test]
+	fn zero_denominators_are_refused() {
+		assert_eq!(Rational::new(BigInt::from(1), BigInt::from(0)), None);
+		assert_eq!(Rational::new(BigInt::from(0), BigInt::from(0)), None);
+		assert_eq!(ratio(1, 2).checked_div(&ratio(0, 3)), None);
+		assert_eq!(ratio(1, 2).checked_div(&ratio(-1, 4)), Some(ratio(-2, 1)));
+	}
+
+	#[test]
+	fn arithmetic_stays_reduced() {
+		assert_eq!((&ratio(1, 3) + &ratio(1, 6)).to_string(), "1/2");
+		assert_eq!((&ratio(1, 3) - &ratio(1, 3)).to_string(), "0/1");
+		assert_eq!((&ratio(2, 3) * &ratio(3, 4)).to_string(), "1/2");
+		assert_eq!((-&ratio(1, 2)).to_string(), "-1/2");
+		assert_eq!(ratio(-2, 3).pow(3).to_string(), "-8/27");
+	}
+
+	#[test]
+	fn floor_rounds_towards_negative_infinity() {
+		assert_eq!(ratio(7, 2).floor(), BigInt::from(3));
+		assert_eq!(ratio(-7, 2).floor(), BigInt::from(-4));
+		assert_eq!(ratio(-8, 2).floor(), BigInt::from(-4));
+		assert!(ratio(1, 3) < ratio(1, 2) && ratio(-1, 2) < ratio(-1, 3));
+		assert_eq!(ratio(1, 4).to_f64(), 0.25);
+	}
+}
@@ -0,0 +1,276 @@
+use std::rc::Rc;
+
+use super::{Function, Instruction, Slot};
+use crate::interpreter::{
+	error::Span,
+	parser::{ASTNode, Operation, Parameter, Value},
+};
+
+// the variables visible inside one function being compiled
+struct Scope {
+	locals: Vec<(String, usize)>,
+	slots: usize,
+	captures: Vec<(String, Slot)>,
+}
+
+struct Compiler {
+	scopes: Vec<Scope>,
+}
+
+impl Compiler {
+	fn scope(&mut self) -> &mut Scope {
+		self.scopes.last_mut().expect("compiling outside of a function")
+	}
+
+	fn declare(&mut self, name: String) -> usize {
+		let scope: &mut Scope = self.scope();
+		let slot: usize = scope.slots;
+		scope.locals.push((name, slot));
+		scope.slots += 1;
+		slot
+	}
+
+	// finds a variable in the function at `depth`, capturing it from the enclosing
+	// functions when needed. `None` means it is a global
+	fn resolve(&mut self, depth: usize, name: &str) -> Option<Slot> {
+		let scope: &Scope = &self.scopes[depth];
+		if let Some((_, slot)) = scope.locals.iter().rev().find(|(local, _): &&(String, usize)| local == name) {
+			return Some(Slot::Local(*slot));
+		}
+
+		if let Some(index) = scope.captures.iter().position(|(captured, _): &(String, Slot)| captured == name) {
+			return Some(Slot::Captured(index));
+		}
+
+		let outer: Slot = self.resolve(depth.checked_sub(1)?, name)?;
+		let captures: &mut Vec<(String, Slot)> = &mut self.scopes[depth].captures;
+		captures.push((name.to_owned(), outer));
+		Some(Slot::Captured(captures.len() - 1))
+	}
+
+	fn variable(&mut self, name: String, span: Span, code: &mut Vec<Instruction>) {
+		code.push(match self.resolve(self.scopes.len() - 1, name.as_str()) {
+			Some(Slot::Local(slot)) => Instruction::Local(slot),
+			Some(Slot::Captured(index)) => Instruction::Captured(index),
+			None => Instruction::Global(name, span),
+		});
+	}
+
+	fn function(&mut self, params: Vec<Parameter>, body: ASTNode) -> Function {
+		self.scopes.push(Scope {
+			locals: params.iter().map(|param: &Parameter| param.name.clone()).zip(0..).collect(),
+			slots: params.len(),
+			captures: vec![],
+		});
+
+		let mut code: Vec<Instruction> = vec![];
+		self.node(body, &mut code, true);
+		code.push(Instruction::Return);
+
+		let scope: Scope = self.scopes.pop().expect("compiling outside of a function");
+		Function {
+			params,
+			slots: scope.slots,
+			captures: scope.captures.into_iter().map(|(_, slot): (String, Slot)| slot).collect(),
+			code,
+		}
+	}
+
+	fn value(&mut self, value: Value, code: &mut Vec<Instruction>) {
+		match value {
+			Value::Variable(name, span) => self.variable(name, span, code),
+			Value::Lambda {args_def, content, ..} => {
+				let function: Function = self.function(args_def, *content);
+				code.push(Instruction::Closure(Rc::new(function)));
+			}
+
+			other => code.push(Instruction::Push(other)),
+		}
+	}
+
+	fn call(&mut self, args: Vec<ASTNode>, name: Option<String>, span: Span, tail: bool, code: &mut Vec<Instruction>) {
+		let argc: usize = args.len();
+		for (position, arg) in args.into_iter().enumerate() {
+			// literals and lambdas are the same whether passed lazily or not
+			if let ASTNode::Value(value) = &arg {
+				if !matches!(value, Value::Variable(..)) {
+					self.node(arg, code, false);
+					continue;
+				}
+			}
+
+			let check: usize = code.len();
+			code.push(Instruction::Lazy {position, end: 0});
+			self.node(arg, code, false);
+			code.push(Instruction::Resume);
+			code[check] = Instruction::Lazy {position, end: code.len()};
+		}
+
+		code.push(Instruction::Call {argc, name, span, tail});
+	}
+
+	// `tail` is set when the value of `node` is the value of the whole function,
+	// so a call there can replace the running frame instead of pushing a new one
+	fn node(&mut self, node: ASTNode, code: &mut Vec<Instruction>, tail: bool) {
+		match node {
+			ASTNode::Nothing => code.push(Instruction::Push(Value::None)),
+			ASTNode::Value(value) => self.value(value, code),
+
+			ASTNode::Array(items, span) => {
+				let len: usize = items.len();
+				for item in items {
+					self.node(item, code, false);
+				}
+
+				code.push(Instruction::Array(len, span));
+			}
+
+			ASTNode::Definition {name, value, span} => {
+				self.node(*value, code, false);
+				code.push(Instruction::Define(name, span));
+			}
+
+			ASTNode::Let {name, value, body} => {
+				self.node(*value, code, false);
+				let slot: usize = self.declare(name);
+				code.push(Instruction::SetLocal(slot));
+				self.node(*body, code, tail);
+				self.scope().locals.pop();
+			}
+
+			ASTNode::LambdaCall {lambda, args, span} => {
+				self.value(*lambda, code);
+				self.call(args, None, span, tail, code);
+			}
+
+			ASTNode::Call {name, args, span} => {
+				self.variable(name.clone(), span, code);
+				self.call(args, Some(name), span, tail, code);
+			}
+
+			ASTNode::Switch {compared, cases} => {
+				self.node(*compared, code, false);
+
+				let mut ends: Vec<usize> = vec![];
+				for case in cases {
+					// pins are evaluated in the scope of the switch, before the pattern binds anything
+					let pins: usize = case.pattern.pins().len();
+					for pin in case.pattern.pins() {
+						self.node(pin.clone(), code, false);
+					}
+
+					let names: Vec<String> = case.pattern.names();
+					let len: usize = names.len();
+					let slots: Vec<usize> = names.into_iter().map(|name: String| self.declare(name)).collect();
+
+					let check: usize = code.len();
+					code.push(Instruction::Pop);
+
+					let guard: Option<usize> = case.guard.map(|guard: ASTNode| {
+						self.node(guard, code, false);
+						code.push(Instruction::Guard {next: 0, span: case.span});
+						code.len() - 1
+					});
+
+					// the subject stays on the stack until a case is taken
+					code.push(Instruction::Pop);
+					self.node(case.action, code, tail);
+					let scope: &mut Scope = self.scope();
+					scope.locals.truncate(scope.locals.len() - len);
+
+					ends.push(code.len());
+					code.push(Instruction::Jump(0));
+
+					let next: usize = code.len();
+					code[check] = Instruction::Match {pattern: case.pattern, pins, slots, next};
+					if let Some(guard) = guard {
+						code[guard] = Instruction::Guard {next, span: case.span};
+					}
+				}
+
+				code.push(Instruction::Pop);
+				code.push(Instruction::Push(Value::None));
+
+				for end in ends {
+					code[end] = Instruction::Jump(code.len());
+				}
+			}
+
+			ASTNode::RationalPart(value, span) => {
+				self.node(*value, code, false);
+				code.push(Instruction::RationalPart(span));
+			}
+
+			ASTNode::IntegerPart(value, span) => {
+				self.node(*value, code, false);
+				code.push(Instruction::IntegerPart(span));
+			}
+
+			ASTNode::Length(value, span) => {
+				self.node(*value, code, false);
+				code.push(Instruction::Length(span));
+			}
+
+			ASTNode::Print(value, span) => {
+				self.node(*value, code, false);
+				code.push(Instruction::Print(span));
+			}
+
+			ASTNode::Import {path, namespace, span} => code.push(Instruction::Import {path, namespace, span}),
+			ASTNode::Input {numeric, span} => code.push(Instruction::Input {numeric, span}),
+
+			ASTNode::Operation {left, operation: operation @ (Operation::And | Operation::Or), right, span} => {
+				let when: bool = operation == Operation::Or;
+				self.node(*left, code, false);
+				code.push(Instruction::Truth(operation.clone(), span));
+				let check: usize = code.len();
+				code.push(Instruction::JumpIf {when, target: 0});
+
+				self.node(*right, code, false);
+				code.push(Instruction::Truth(operation, span));
+				code[check] = Instruction::JumpIf {when, target: code.len()};
+			}
+
+			ASTNode::Operation {left, operation, right, span} => {
+				self.node(*left, code, false);
+				self.node(*right, code, false);
+				code.push(Instruction::Operation(operation, span));
+			}
+		}
+	}
+}
+
+// compiles top-level statements into a function without parameters returning the value of the last one.
+// lambda parameters and let bindings get resolved to frame slots, everything else is looked up as a global
+pub fn compile(nodes: Vec<ASTNode>) -> Function {
+	let mut compiler: Compiler = Compiler {
+		scopes: vec![Scope {
+			locals: vec![],
+			slots: 0,
+			captures: vec![],
+		}],
+	};
+
+	let mut code: Vec<Instruction> = vec![];
+	let len: usize = nodes.len();
+	for (index, node) in nodes.into_iter().enumerate() {
+		compiler.node(node, &mut code, false);
+		if index + 1 < len {
+			code.push(Instruction::Pop);
+		}
+	}
+
+	if len == 0 {
+		code.push(Instruction::Push(Value::None));
+	}
+
+	code.push(Instruction::Return);
+
+	let scope: Scope = compiler.scopes.pop().expect("compiling outside of a function");
+	Function {
+		params: vec![],
+		slots: scope.slots,
+		captures: vec![],
+		code,
+	}
+}
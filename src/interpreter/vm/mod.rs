@@ -0,0 +1,472 @@
+pub mod compiler;
+use std::{cell::RefCell, mem, rc::Rc};
+
+use super::{
+	apply_function,
+	error::{LambisError, Span},
+	import,
+	integer_part,
+	length,
+	condition,
+	matches,
+	build,
+	parser::{pattern::Pattern, Operation, Parameter, Thunk, Value},
+	rational_part,
+	read_input,
+	truth,
+	undefined_variable,
+	Environment,
+};
+
+// where a variable lives at run time: a slot of the current frame,
+// or one of the values the running closure captured when it was created
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Slot {
+	Local(usize),
+	Captured(usize),
+}
+
+#[derive(Debug, Clone)]
+pub enum Instruction {
+	Push(Value),
+	Local(usize),
+	Captured(usize),
+	Global(String, Span),
+	SetLocal(usize),
+	Define(String, Span),
+	Closure(Rc<Function>),
+	Array(usize, Span),
+
+	// calls the value below `argc` arguments, reusing the current frame when `tail` is set
+	Call {
+		argc: usize,
+		name: Option<String>,
+		span: Span,
+		tail: bool
+	},
+
+	Operation(Operation, Span),
+	// checks that the operand of `&&` or `||` on top of the stack is a boolean
+	Truth(Operation, Span),
+	// jumps keeping the boolean on top of the stack when it is `when`, pops it otherwise
+	JumpIf {
+		when: bool,
+		target: usize
+	},
+	Length(Span),
+	IntegerPart(Span),
+	RationalPart(Span),
+	Print(Span),
+	Input {
+		numeric: bool,
+		span: Span
+	},
+	Import {
+		path: String,
+		namespace: Option<String>,
+		span: Span
+	},
+
+	// when the parameter the argument at `position` is passed to is lazy, pushes the
+	// argument code starting after this instruction as a thunk and jumps to `end`
+	Lazy {
+		position: usize,
+		end: usize
+	},
+	// ends the code of an argument, which finishes the frame forcing it as a thunk
+	Resume,
+
+	// pops what the `pins` pinned expressions of the pattern evaluated to, then matches the switch
+	// subject under them, storing what the pattern binds into `slots` on a match and jumping to the next case otherwise
+	Match {
+		pattern: Pattern,
+		pins: usize,
+		slots: Vec<usize>,
+		next: usize
+	},
+	// pops the value of a case guard, jumping to the next case when it is false
+	Guard {
+		next: usize,
+		span: Span
+	},
+	Jump(usize),
+	Pop,
+	Return,
+}
+
+// spans are left out, like in the syntax tree the code is compiled from
+impl PartialEq for Instruction {
+	fn eq(&self, other: &Self) -> bool {
+		match (self, other) {
+			(Instruction::Push(a), Instruction::Push(b)) => a == b,
+			(Instruction::Local(a), Instruction::Local(b))
+			| (Instruction::Captured(a), Instruction::Captured(b))
+			| (Instruction::SetLocal(a), Instruction::SetLocal(b))
+			| (Instruction::Array(a, _), Instruction::Array(b, _))
+			| (Instruction::Jump(a), Instruction::Jump(b)) => a == b,
+			(Instruction::Global(a, _), Instruction::Global(b, _))
+			| (Instruction::Define(a, _), Instruction::Define(b, _)) => a == b,
+			(Instruction::Closure(a), Instruction::Closure(b)) => a == b,
+			(
+				Instruction::Call {argc, name, tail, ..},
+				Instruction::Call {argc: other_argc, name: other_name, tail: other_tail, ..},
+			) => argc == other_argc && name == other_name && tail == other_tail,
+			(Instruction::Operation(a, _), Instruction::Operation(b, _))
+			| (Instruction::Truth(a, _), Instruction::Truth(b, _)) => a == b,
+			(Instruction::JumpIf {when, target}, Instruction::JumpIf {when: other_when, target: other_target}) => {
+				when == other_when && target == other_target
+			}
+			(Instruction::Input {numeric, ..}, Instruction::Input {numeric: other, ..}) => numeric == other,
+			(
+				Instruction::Import {path, namespace, ..},
+				Instruction::Import {path: other_path, namespace: other_namespace, ..},
+			) => path == other_path && namespace == other_namespace,
+			(Instruction::Lazy {position, end}, Instruction::Lazy {position: other_position, end: other_end}) => {
+				position == other_position && end == other_end
+			}
+			(
+				Instruction::Match {pattern, pins, slots, next},
+				Instruction::Match {pattern: other_pattern, pins: other_pins, slots: other_slots, next: other_next},
+			) => pattern == other_pattern && pins == other_pins && slots == other_slots && next == other_next,
+			(Instruction::Guard {next, ..}, Instruction::Guard {next: other, ..}) => next == other,
+			// the rest only hold a span, if anything
+			_ => mem::discriminant(self) == mem::discriminant(other),
+		}
+	}
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Function {
+	pub params: Vec<Parameter>,
+	pub slots: usize,
+	pub captures: Vec<Slot>,
+	pub code: Vec<Instruction>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Closure {
+	pub function: Rc<Function>,
+	pub captured: Vec<Value>,
+	pub applied: Vec<Value>,
+}
+
+impl Closure {
+	// parameters still waiting for an argument
+	pub fn remaining(&self) -> &[Parameter] {
+		&self.function.params[self.applied.len()..]
+	}
+}
+
+struct Frame {
+	closure: Rc<Closure>,
+	ip: usize,
+	locals: Vec<Value>,
+	base: usize,
+	// the lazy argument this frame is evaluating and where its code ends
+	thunk: Option<(Rc<RefCell<Thunk>>, usize)>,
+}
+
+impl Frame {
+	fn new(closure: Rc<Closure>, mut locals: Vec<Value>, base: usize) -> Self {
+		locals.resize(closure.function.slots, Value::None);
+
+		Self {
+			closure,
+			ip: 0,
+			locals,
+			base,
+			thunk: None,
+		}
+	}
+}
+
+struct Machine {
+	stack: Vec<Value>,
+	frames: Vec<Frame>,
+}
+
+impl Machine {
+	fn pop(&mut self) -> Value {
+		self.stack.pop().expect("the compiler emitted code that pops an empty stack")
+	}
+
+	fn frame(&mut self) -> &mut Frame {
+		self.frames.last_mut().expect("the compiler emitted code running outside of a frame")
+	}
+
+	// pushes the value of a variable, evaluating it first when it is an unforced lazy argument
+	fn load(&mut self, value: Value) {
+		let Value::Thunk(thunk) = value else {
+			self.stack.push(value);
+			return;
+		};
+
+		let frame: Frame = match &*thunk.borrow() {
+			Thunk::Forced(value) => {
+				self.stack.push(value.clone());
+				return;
+			}
+
+			Thunk::Code {closure, locals, ip, end} => Frame {
+				closure: Rc::clone(closure),
+				ip: *ip,
+				locals: locals.clone(),
+				base: self.stack.len(),
+				thunk: Some((Rc::clone(&thunk), *end)),
+			},
+
+			Thunk::Node(..) => unreachable!("syntax tree arguments are forced by the tree-walking interpreter"),
+		};
+
+		self.frames.push(frame);
+	}
+
+	fn call(&mut self, callee: Value, args: Vec<Value>, name: Option<&str>, span: Span, tail: bool, env: &mut Environment) -> Result<(), LambisError> {
+		env.step(span)?;
+
+		match callee {
+			Value::Closure(closure) => {
+				let len: usize = args.len();
+				let len2: usize = closure.remaining().len();
+				if len > len2 {
+					return Err(LambisError::runtime(format!("too much arguments ({len}/{len2}) for «{}»", Value::Closure(closure)), span));
+				}
+
+				let mut applied: Vec<Value> = closure.applied.clone();
+				applied.extend(args);
+
+				if len < len2 {
+					self.stack.push(Value::Closure(Rc::new(Closure {
+						function: Rc::clone(&closure.function),
+						captured: closure.captured.clone(),
+						applied,
+					})));
+				} else if tail {
+					let frame: &mut Frame = self.frame();
+					*frame = Frame::new(closure, applied, frame.base);
+				} else {
+					env.enter(span)?;
+					let base: usize = self.stack.len();
+					self.frames.push(Frame::new(closure, applied, base));
+				}
+			}
+
+			function @ (Value::Builtin {..} | Value::Native {..}) => {
+				let result: Value = apply_function(function, args, span, env)?;
+				self.stack.push(result);
+			}
+
+			other => {
+				let name: String = name.map_or_else(|| other.to_string(), str::to_owned);
+				return Err(LambisError::runtime(format!("trying to call «{name}», which is {other}"), span)
+					.with_hint("only lambdas and builtins can be called"));
+			}
+		}
+
+		Ok(())
+	}
+
+	fn run(&mut self, env: &mut Environment) -> Result<Value, LambisError> {
+		loop {
+			let frame: &mut Frame = self.frame();
+			let function: Rc<Function> = Rc::clone(&frame.closure.function);
+			let instruction: &Instruction = &function.code[frame.ip];
+			frame.ip += 1;
+
+			match instruction {
+				Instruction::Push(value) => self.stack.push(value.clone()),
+
+				Instruction::Local(slot) => {
+					let value: Value = frame.locals[*slot].clone();
+					self.load(value);
+				}
+
+				Instruction::Captured(index) => {
+					let value: Value = frame.closure.captured[*index].clone();
+					self.load(value);
+				}
+
+				Instruction::Global(name, span) => match env.variables.get(name) {
+					Some(value) => self.load(value.clone()),
+					None => return Err(undefined_variable(name, env.variables.keys().map(String::as_str), *span)),
+				}
+
+				Instruction::SetLocal(slot) => {
+					let slot: usize = *slot;
+					let value: Value = self.pop();
+					self.frame().locals[slot] = value;
+				}
+
+				Instruction::Define(name, span) => {
+					env.definable(name, *span)?;
+					let value: Value = self.stack.last().cloned().unwrap_or(Value::None);
+					env.define(name.clone(), value);
+				}
+
+				Instruction::Closure(function) => {
+					let captured: Vec<Value> = function.captures
+						.iter()
+						.map(|slot: &Slot| match *slot {
+							Slot::Local(index) => frame.locals[index].clone(),
+							Slot::Captured(index) => frame.closure.captured[index].clone(),
+						}).collect();
+
+					self.stack.push(Value::Closure(Rc::new(Closure {
+						function: Rc::clone(function),
+						captured,
+						applied: vec![],
+					})));
+				}
+
+				Instruction::Array(len, span) => {
+					env.allocate(*len as u64, *span)?;
+					let items: Vec<Value> = self.stack.split_off(self.stack.len() - len);
+					self.stack.push(Value::Array(items));
+				}
+
+				Instruction::Call {argc, name, span, tail} => {
+					let args: Vec<Value> = self.stack.split_off(self.stack.len() - argc);
+					let callee: Value = self.pop();
+					self.call(callee, args, name.as_deref(), *span, *tail, env)?;
+				}
+
+				Instruction::Operation(op, span) => {
+					let right: Value = self.pop();
+					let left: Value = self.pop();
+					self.stack.push(build(op, left, right, *span, env)?);
+				}
+
+				Instruction::Truth(op, span) => {
+					let value: Value = self.pop();
+					self.stack.push(Value::Bool(truth(op, value, *span)?));
+				}
+
+				Instruction::JumpIf {when, target} => {
+					let target: usize = *target;
+					if self.stack.last() == Some(&Value::Bool(*when)) {
+						self.frame().ip = target;
+					} else {
+						self.pop();
+					}
+				}
+
+				Instruction::Length(span) => {
+					let value: Value = self.pop();
+					self.stack.push(length(value, *span)?);
+				}
+
+				Instruction::IntegerPart(span) => {
+					let value: Value = self.pop();
+					self.stack.push(integer_part(value, *span)?);
+				}
+
+				Instruction::RationalPart(span) => {
+					let value: Value = self.pop();
+					self.stack.push(rational_part(value, *span)?);
+				}
+
+				Instruction::Print(span) => {
+					env.permit("printing", *span)?;
+					println!("{}", self.pop());
+					self.stack.push(Value::None);
+				}
+
+				Instruction::Input {numeric, span} => {
+					env.permit("reading input", *span)?;
+					self.stack.push(read_input(*numeric, *span)?);
+				}
+
+				Instruction::Import {path, namespace, span} => {
+					env.permit("importing", *span)?;
+					import(path.as_str(), namespace.clone(), *span, env)?;
+					self.stack.push(Value::None);
+				}
+
+				Instruction::Lazy {position, end} => {
+					let callee: &Value = &self.stack[self.stack.len() - 1 - position];
+					let lazy: bool = matches!(callee, Value::Closure(closure)
+						if closure.remaining().get(*position).is_some_and(|param: &Parameter| param.lazy));
+
+					if lazy {
+						let frame: &mut Frame = self.frame();
+						let thunk: Thunk = Thunk::Code {
+							closure: Rc::clone(&frame.closure),
+							locals: frame.locals.clone(),
+							ip: frame.ip,
+							end: *end,
+						};
+
+						frame.ip = *end;
+						self.stack.push(Value::Thunk(Rc::new(RefCell::new(thunk))));
+					}
+				}
+
+				Instruction::Resume => {
+					let ip: usize = frame.ip;
+					if let Some((thunk, _)) = frame.thunk.take_if(|(_, end): &mut (Rc<RefCell<Thunk>>, usize)| *end == ip) {
+						let value: Value = self.pop();
+						let frame: Frame = self.frames.pop().expect("the compiler emitted a return outside of a frame");
+						self.stack.truncate(frame.base);
+						thunk.replace(Thunk::Forced(value.clone()));
+						self.stack.push(value);
+					}
+				}
+
+				Instruction::Match {pattern, pins, slots, next} => {
+					let pins: Vec<Value> = self.stack.split_off(self.stack.len() - pins);
+					let compared: &Value = self.stack.last().expect("the compiler emitted a case without a subject");
+					let mut bound: Vec<Value> = vec![];
+
+					if matches(pattern, compared, &mut pins.into_iter(), &mut bound) {
+						let frame: &mut Frame = self.frame();
+						for (&slot, value) in slots.iter().zip(bound) {
+							frame.locals[slot] = value;
+						}
+					} else {
+						self.frame().ip = *next;
+					}
+				}
+
+				Instruction::Guard {next, span} => {
+					let value: Value = self.pop();
+					if !condition("when", value, *span)? {
+						self.frame().ip = *next;
+					}
+				}
+
+				Instruction::Jump(target) => frame.ip = *target,
+
+				Instruction::Pop => {
+					self.pop();
+				}
+
+				Instruction::Return => {
+					let result: Value = self.pop();
+					let frame: Frame = self.frames.pop().expect("the compiler emitted a return outside of a frame");
+					self.stack.truncate(frame.base);
+
+					if self.frames.is_empty() {
+						return Ok(result);
+					}
+
+					env.leave();
+					self.stack.push(result);
+				}
+			}
+		}
+	}
+}
+
+// runs a compiled script, see `compiler::compile`
+pub fn run(main: Function, env: &mut Environment) -> Result<Value, LambisError> {
+	let closure: Rc<Closure> = Rc::new(Closure {
+		function: Rc::new(main),
+		captured: vec![],
+		applied: vec![],
+	});
+
+	Machine {
+		stack: vec![],
+		frames: vec![Frame::new(closure, vec![], 0)],
+	}.run(env)
+}
@@ -0,0 +1,477 @@
+use std::{cell::RefCell, collections::HashMap, rc::Rc, vec};
+
+use super::{
+	apply_function,
+	build,
+	condition,
+	error::{LambisError, Span},
+	import,
+	integer_part,
+	length,
+	matches,
+	parser::{pattern::Case, ASTNode, Operation, Parameter, Thunk, Value},
+	rational_part,
+	read_input,
+	truth,
+	undefined_variable,
+	Environment,
+};
+
+// the bindings an expression is evaluated with, shared by the expressions evaluated in the same place
+type Scope = Rc<HashMap<String, Value>>;
+
+// what is left to do with the value of the expression evaluated last. Expressions waiting
+// for the value of another one are kept here instead of on the native stack, so only
+// memory and `Limits::depth` bound how deep a script can recurse
+enum Continuation {
+	Print(Span),
+	Length(Span),
+	RationalPart(Span),
+	IntegerPart(Span),
+	// the items left to evaluate, after the values of those before them
+	Array {
+		items: vec::IntoIter<ASTNode>,
+		values: Vec<Value>,
+		scope: Scope,
+		span: Span,
+	},
+	// the subject of a switch, before its cases are tried
+	Switch {
+		cases: vec::IntoIter<Case>,
+		scope: Scope,
+	},
+	// a pin of `case`, after the pins before it
+	Pin {
+		compared: Value,
+		case: Box<Case>,
+		pins: Vec<Value>,
+		cases: vec::IntoIter<Case>,
+		scope: Scope,
+	},
+	// the guard of a case whose pattern matched, which runs `action` in `bindings` when it holds
+	Guard {
+		compared: Value,
+		action: ASTNode,
+		span: Span,
+		bindings: Scope,
+		cases: vec::IntoIter<Case>,
+		scope: Scope,
+	},
+	Define(String),
+	Let {
+		name: String,
+		body: ASTNode,
+		scope: Scope,
+	},
+	// the function a call applies, before its arguments are evaluated
+	Callee {
+		name: String,
+		args: Vec<ASTNode>,
+		scope: Scope,
+		span: Span,
+	},
+	// the arguments left to evaluate, after the values of those before them
+	Arguments {
+		function: Value,
+		args: vec::IntoIter<ASTNode>,
+		values: Vec<Value>,
+		scope: Scope,
+		span: Span,
+	},
+	// the left side of an operation, before the right one is evaluated
+	Left {
+		op: Operation,
+		right: ASTNode,
+		scope: Scope,
+		span: Span,
+	},
+	Right {
+		op: Operation,
+		left: Value,
+		span: Span,
+	},
+	// the right side of `&&` or `||`
+	Truth(Operation, Span),
+	// a lazy argument evaluated for the first time
+	Force(Rc<RefCell<Thunk>>),
+	// the body of a call that isn't a tail call
+	Return,
+}
+
+// what the walker does next
+enum Next {
+	Eval(ASTNode, Scope),
+	Return(Value),
+}
+
+// the result of applying a lambda: either a closure over the given arguments,
+// or its body together with the scope it has to be evaluated in
+enum Application {
+	Partial(Value),
+	Body(ASTNode, HashMap<String, Value>),
+}
+
+// binds `values` to the lambda parameters on top of its captured environment
+fn apply_lambda(lambda: Value, values: Vec<Value>, span: Span) -> Result<Application, LambisError> {
+	let Value::Lambda {mut args_def, content, mut captured} = lambda else {
+		unreachable!("how the fuck you managed to call non-lambda???")
+	};
+
+	let len: usize = values.len();
+	let len2: usize = args_def.len();
+	if len > len2 {
+		return Err(LambisError::runtime(format!("too much arguments ({len}/{len2}) for «<λ{}.>»", super::parser::parameters(&args_def)), span));
+	}
+
+	let rest: Vec<Parameter> = args_def.split_off(len);
+	for (param, value) in args_def.into_iter().zip(values) {
+		captured.insert(param.name, value);
+	}
+
+	Ok(if rest.is_empty() {
+		Application::Body(*content, captured)
+	} else {
+		Application::Partial(Value::Lambda {
+			args_def: rest,
+			content,
+			captured
+		})
+	})
+}
+
+// `scope` extended with `names` bound to `values`, or `scope` itself when there are none
+fn extended(scope: &Scope, names: impl IntoIterator<Item = (String, Value)>) -> Scope {
+	let mut names = names.into_iter().peekable();
+	if names.peek().is_none() {
+		return Rc::clone(scope);
+	}
+
+	let mut bindings: HashMap<String, Value> = (**scope).clone();
+	bindings.extend(names);
+	Rc::new(bindings)
+}
+
+struct Walker<'a> {
+	env: &'a mut Environment,
+	continuations: Vec<Continuation>,
+}
+
+impl Walker<'_> {
+
+	fn run(&mut self, node: ASTNode, scope: Scope) -> Result<Value, LambisError> {
+		let mut next: Next = Next::Eval(node, scope);
+		loop {
+			next = match next {
+				Next::Eval(node, scope) => self.eval(node, scope)?,
+				Next::Return(value) => match self.continuations.pop() {
+					Some(continuation) => self.resume(continuation, value)?,
+					None => return Ok(value),
+				},
+			};
+		}
+	}
+
+	fn eval(&mut self, node: ASTNode, scope: Scope) -> Result<Next, LambisError> {
+		Ok(match node {
+			ASTNode::Nothing => Next::Return(Value::None),
+
+			ASTNode::Print(value, span) => {
+				self.continuations.push(Continuation::Print(span));
+				Next::Eval(*value, scope)
+			}
+
+			ASTNode::Input {numeric, span} => {
+				self.env.permit("reading input", span)?;
+				Next::Return(read_input(numeric, span)?)
+			}
+
+			ASTNode::Import {path, namespace, span} => {
+				self.env.permit("importing", span)?;
+				import(path.as_str(), namespace, span, self.env)?;
+				Next::Return(Value::None)
+			}
+
+			ASTNode::Array(items, span) => {
+				let mut items: vec::IntoIter<ASTNode> = items.into_iter();
+				match items.next() {
+					Some(first) => {
+						self.continuations.push(Continuation::Array {items, values: vec![], scope: Rc::clone(&scope), span});
+						Next::Eval(first, scope)
+					}
+					None => Next::Return(Value::Array(vec![])),
+				}
+			}
+
+			ASTNode::Length(value, span) => {
+				self.continuations.push(Continuation::Length(span));
+				Next::Eval(*value, scope)
+			}
+
+			ASTNode::RationalPart(value, span) => {
+				self.continuations.push(Continuation::RationalPart(span));
+				Next::Eval(*value, scope)
+			}
+
+			ASTNode::IntegerPart(value, span) => {
+				self.continuations.push(Continuation::IntegerPart(span));
+				Next::Eval(*value, scope)
+			}
+
+			ASTNode::Switch {compared, cases} => {
+				self.continuations.push(Continuation::Switch {cases: cases.into_iter(), scope: Rc::clone(&scope)});
+				Next::Eval(*compared, scope)
+			}
+
+			ASTNode::Definition {name, value, span} => {
+				self.env.definable(&name, span)?;
+				self.continuations.push(Continuation::Define(name));
+				Next::Eval(*value, scope)
+			}
+
+			ASTNode::Let {name, value, body} => {
+				self.continuations.push(Continuation::Let {name, body: *body, scope: Rc::clone(&scope)});
+				Next::Eval(*value, scope)
+			}
+
+			ASTNode::Value(Value::Variable(name, span)) => {
+				let value: Value = match scope.get(&name).or_else(|| self.env.variables.get(&name)) {
+					Some(value) => value.clone(),
+					None => return Err(undefined_variable(
+						name.as_str(),
+						self.env.variables.keys().chain(scope.keys()).map(String::as_str),
+						span
+					)),
+				};
+
+				self.force(value)?
+			}
+
+			// a lambda literal closes over the arguments visible where it is created
+			ASTNode::Value(Value::Lambda {args_def, content, captured}) => {
+				let mut closure: HashMap<String, Value> = (*scope).clone();
+				closure.extend(captured);
+				Next::Return(Value::Lambda {args_def, content, captured: closure})
+			}
+
+			ASTNode::Value(value) => Next::Return(value),
+
+			ASTNode::LambdaCall {lambda, args, span} => {
+				self.env.step(span)?;
+				let Value::Lambda {args_def, content, captured} = *lambda else {
+					unreachable!("lambda literals evaluate to lambdas")
+				};
+
+				let mut closure: HashMap<String, Value> = (*scope).clone();
+				closure.extend(captured);
+				self.arguments(Value::Lambda {args_def, content, captured: closure}, args.into_iter(), vec![], scope, span)?
+			}
+
+			ASTNode::Call {name, args, span} => {
+				self.env.step(span)?;
+				self.continuations.push(Continuation::Callee {name: name.clone(), args, scope: Rc::clone(&scope), span});
+				Next::Eval(ASTNode::Value(Value::Variable(name, span)), scope)
+			}
+
+			ASTNode::Operation {left, operation, right, span} => {
+				self.continuations.push(Continuation::Left {op: operation, right: *right, scope: Rc::clone(&scope), span});
+				Next::Eval(*left, scope)
+			}
+		})
+	}
+
+	fn resume(&mut self, continuation: Continuation, value: Value) -> Result<Next, LambisError> {
+		Ok(match continuation {
+			Continuation::Print(span) => {
+				self.env.permit("printing", span)?;
+				println!("{value}");
+				Next::Return(Value::None)
+			}
+
+			Continuation::Length(span) => Next::Return(length(value, span)?),
+			Continuation::RationalPart(span) => Next::Return(rational_part(value, span)?),
+			Continuation::IntegerPart(span) => Next::Return(integer_part(value, span)?),
+
+			Continuation::Array {mut items, mut values, scope, span} => {
+				values.push(value);
+				match items.next() {
+					Some(item) => {
+						self.continuations.push(Continuation::Array {items, values, scope: Rc::clone(&scope), span});
+						Next::Eval(item, scope)
+					}
+					None => {
+						self.env.allocate(values.len() as u64, span)?;
+						Next::Return(Value::Array(values))
+					}
+				}
+			}
+
+			Continuation::Switch {cases, scope} => self.cases(value, cases, scope)?,
+
+			Continuation::Pin {compared, case, mut pins, cases, scope} => {
+				pins.push(value);
+				self.case(compared, *case, pins, cases, scope)?
+			}
+
+			Continuation::Guard {compared, action, span, bindings, cases, scope} => {
+				if condition("when", value, span)? {
+					Next::Eval(action, bindings)
+				} else {
+					self.cases(compared, cases, scope)?
+				}
+			}
+
+			Continuation::Define(name) => {
+				self.env.define(name, value.clone());
+				Next::Return(value)
+			}
+
+			Continuation::Let {name, body, scope} => Next::Eval(body, extended(&scope, [(name, value)])),
+
+			Continuation::Callee {name, args, scope, span} => match value {
+				function @ (Value::Lambda {..} | Value::Builtin {..} | Value::Native {..}) => {
+					self.arguments(function, args.into_iter(), vec![], scope, span)?
+				}
+
+				other => return Err(LambisError::runtime(format!("trying to call «{name}», which is {other}"), span)
+					.with_hint("only lambdas and builtins can be called")),
+			},
+
+			Continuation::Arguments {function, args, mut values, scope, span} => {
+				values.push(value);
+				self.arguments(function, args, values, scope, span)?
+			}
+
+			Continuation::Left {op: op @ (Operation::And | Operation::Or), right, scope, span} => {
+				let decided: bool = op == Operation::Or;
+				if truth(&op, value, span)? == decided {
+					Next::Return(Value::Bool(decided))
+				} else {
+					self.continuations.push(Continuation::Truth(op, span));
+					Next::Eval(right, scope)
+				}
+			}
+
+			Continuation::Left {op, right, scope, span} => {
+				self.continuations.push(Continuation::Right {op, left: value, span});
+				Next::Eval(right, scope)
+			}
+
+			Continuation::Right {op, left, span} => Next::Return(build(&op, left, value, span, self.env)?),
+			Continuation::Truth(op, span) => Next::Return(Value::Bool(truth(&op, value, span)?)),
+
+			Continuation::Force(thunk) => {
+				thunk.replace(Thunk::Forced(value.clone()));
+				Next::Return(value)
+			}
+
+			Continuation::Return => {
+				self.env.leave();
+				Next::Return(value)
+			}
+		})
+	}
+
+	// evaluates a lazy argument the first time it is needed, later references reuse the result
+	fn force(&mut self, value: Value) -> Result<Next, LambisError> {
+		let Value::Thunk(thunk) = value else {
+			return Ok(Next::Return(value));
+		};
+
+		let (node, scope): (ASTNode, HashMap<String, Value>) = match &*thunk.borrow() {
+			Thunk::Forced(value) => return Ok(Next::Return(value.clone())),
+			Thunk::Node(node, scope) => (node.clone(), scope.clone()),
+			Thunk::Code {..} => unreachable!("compiled arguments are forced by the vm"),
+		};
+
+		// an argument can't refer to itself, every variable it sees already had a value when it was passed
+		self.continuations.push(Continuation::Force(thunk));
+		Ok(Next::Eval(node, Rc::new(scope)))
+	}
+
+	// evaluates the arguments left in `args` one after the other, then applies `function` to them.
+	// Arguments of lazy parameters are left unevaluated together with the scope they are passed from,
+	// except for literals and lambdas, which are the same whether passed lazily or not
+	fn arguments(&mut self, function: Value, mut args: vec::IntoIter<ASTNode>, mut values: Vec<Value>, scope: Scope, span: Span) -> Result<Next, LambisError> {
+		while let Some(arg) = args.next() {
+			let lazy: bool = matches!(&function, Value::Lambda {args_def, ..}
+				if args_def.get(values.len()).is_some_and(|param: &Parameter| param.lazy));
+
+			let literal: bool = matches!(&arg, ASTNode::Value(value) if !matches!(value, Value::Variable(..)));
+			if lazy && !literal {
+				values.push(Value::Thunk(Rc::new(RefCell::new(Thunk::Node(arg, (*scope).clone())))));
+				continue;
+			}
+
+			self.continuations.push(Continuation::Arguments {function, args, values, scope: Rc::clone(&scope), span});
+			return Ok(Next::Eval(arg, scope));
+		}
+
+		Ok(match function {
+			lambda @ Value::Lambda {..} => match apply_lambda(lambda, values, span)? {
+				Application::Partial(closure) => Next::Return(closure),
+				Application::Body(body, bound) => {
+					// the body of a tail call replaces the one waiting for it
+					if !matches!(self.continuations.last(), Some(Continuation::Return)) {
+						self.env.enter(span)?;
+						self.continuations.push(Continuation::Return);
+					}
+
+					Next::Eval(body, Rc::new(bound))
+				}
+			},
+
+			function => Next::Return(apply_function(function, values, span, self.env)?),
+		})
+	}
+
+	// tries the cases left in `cases` one after the other, evaluating nothing when none matches
+	fn cases(&mut self, compared: Value, mut cases: vec::IntoIter<Case>, scope: Scope) -> Result<Next, LambisError> {
+		match cases.next() {
+			Some(case) => self.case(compared, case, vec![], cases, scope),
+			None => Ok(Next::Return(Value::None)),
+		}
+	}
+
+	// evaluates the pins of `case` left after `pins`, then matches it
+	fn case(&mut self, compared: Value, case: Case, pins: Vec<Value>, cases: vec::IntoIter<Case>, scope: Scope) -> Result<Next, LambisError> {
+		if let Some(pin) = case.pattern.pins().get(pins.len()).map(|pin: &&ASTNode| (*pin).clone()) {
+			self.continuations.push(Continuation::Pin {compared, case: Box::new(case), pins, cases, scope: Rc::clone(&scope)});
+			return Ok(Next::Eval(pin, scope));
+		}
+
+		let mut bound: Vec<Value> = vec![];
+		if !matches(&case.pattern, &compared, &mut pins.into_iter(), &mut bound) {
+			return self.cases(compared, cases, scope);
+		}
+
+		// cases without bindings run in the scope of the switch itself
+		let bindings: Scope = extended(&scope, case.pattern.names().into_iter().zip(bound));
+		Ok(match case.guard {
+			Some(guard) => {
+				self.continuations.push(Continuation::Guard {
+					compared,
+					action: case.action,
+					span: case.span,
+					bindings: Rc::clone(&bindings),
+					cases,
+					scope,
+				});
+
+				Next::Eval(guard, bindings)
+			}
+
+			None => Next::Eval(case.action, bindings),
+		})
+	}
+}
+
+// evaluates a top-level statement. Calls in tail position (switch actions, let bodies and
+// lambda bodies) replace the body waiting for them, so deep tail recursion runs in constant space
+pub fn eval(node: ASTNode, env: &mut Environment) -> Result<Value, LambisError> {
+	let depth: usize = env.usage.depth;
+	let result: Result<Value, LambisError> = Walker {env, continuations: vec![]}.run(node, Rc::new(HashMap::new()));
+
+	// the calls an error interrupted never returned
+	env.usage.depth = depth;
+	result
+}
@@ -0,0 +1,123 @@
+//! lambis as a library: an [`Interpreter`] keeps its definitions between evaluations,
+//! and the program embedding it can read and set globals and add functions of its own.
+
+mod format;
+pub(crate) mod interpreter;
+pub mod repl;
+mod value;
+use std::{env, num::NonZeroUsize, path::{Path, PathBuf}, rc::Rc};
+
+pub use interpreter::{bigint::BigInt, error::{LambisError, Limit, Span}, rational::Rational, Backend, Cancellation, Limits};
+use interpreter::{builtin::Native, parser, Environment};
+pub use format::format;
+pub use value::{Function, Value};
+
+/// Evaluates lambis source, keeping the globals it defines for later evaluations.
+///
+/// Functions are values of the backend that made them: a [`Function`] the tree-walker
+/// made can't be called by the bytecode vm, nor the other way around.
+///
+/// Values share their data through `Rc`, so an interpreter is `!Send` and stays on
+/// the thread that made it. Other threads can still stop it with [`Interpreter::cancellation`].
+pub struct Interpreter {
+	environment: Environment,
+}
+
+impl Interpreter {
+	/// Imports are resolved from the current directory.
+	pub fn new() -> Self {
+		Self::with_root(env::current_dir().unwrap_or_else(|_| PathBuf::from(".")))
+	}
+
+	/// Imports are resolved from `root`.
+	pub fn with_root(root: PathBuf) -> Self {
+		Self {
+			environment: Environment::new(root),
+		}
+	}
+
+	/// Choose it before evaluating anything: functions defined until then
+	/// stay values of the previous backend, which the new one can't call.
+	pub fn set_backend(&mut self, backend: Backend) {
+		self.environment.backend = backend;
+	}
+
+	/// Every later evaluation is checked against `limits`.
+	pub fn set_limits(&mut self, limits: Limits) {
+		self.environment.limits = limits;
+	}
+
+	/// A handle other threads can stop the running evaluation of this interpreter with.
+	/// A cancellation made while nothing runs is dropped when the next evaluation starts.
+	pub fn cancellation(&self) -> Cancellation {
+		self.environment.cancellation.clone()
+	}
+
+	/// Evaluates every statement of `source`, returning the value of the last one.
+	/// A function it returns can only be called by the active backend.
+	pub fn eval(&mut self, source: &str) -> Result<Value, LambisError> {
+		self.environment.eval_source(source).map(Value::from_runtime)
+	}
+
+	/// Like [`Interpreter::eval`], with the imports of `source` resolved from the directory of `path`.
+	pub fn eval_file(&mut self, path: &Path, source: &str) -> Result<Value, LambisError> {
+		self.environment.eval_file(path, source).map(Value::from_runtime)
+	}
+
+	pub fn get(&self, name: &str) -> Option<Value> {
+		self.environment.variables.get(name).cloned().map(Value::from_runtime)
+	}
+
+	/// Unlike a definition in a script, this replaces a global that already exists.
+	/// Values holding a function the active backend can't call are refused.
+	pub fn set(&mut self, name: impl Into<String>, value: Value) -> Result<(), LambisError> {
+		let name: String = name.into();
+		let value: parser::Value = value.into_runtime();
+		if !self.environment.backend.can_call(&value) {
+			let backend: &str = match self.environment.backend {
+				Backend::TreeWalker => "tree-walker",
+				Backend::Vm => "vm",
+			};
+
+			return Err(LambisError::runtime(format!("«{name}» holds a function the {backend} can't call"), Span::default())
+				.with_hint("functions can only be set on an interpreter with the backend that made them"));
+		}
+
+		self.environment.variables.insert(name, value);
+		Ok(())
+	}
+
+	/// Scripts call `function` like a builtin once it has `arity` arguments, there is
+	/// no calling it without any. An `Err` it returns is reported as a runtime error at the call.
+	pub fn register(
+		&mut self,
+		name: impl Into<String>,
+		arity: NonZeroUsize,
+		function: impl Fn(Vec<Value>) -> Result<Value, String> + 'static,
+	) {
+		let name: String = name.into();
+		let native: Native = Native {
+			name: name.clone(),
+			arity: arity.get(),
+			function: Box::new(move |args: Vec<parser::Value>| {
+				function(args.into_iter().map(Value::from_runtime).collect()).map(Value::into_runtime)
+			}),
+		};
+
+		self.environment.variables.insert(name, parser::Value::Native {
+			native: Rc::new(native),
+			applied: vec![],
+		});
+	}
+
+	/// The name and source of the file an error points into, when it comes from an imported module.
+	pub fn source_of(&self, error: &LambisError) -> Option<(String, &str)> {
+		self.environment.source_of(error)
+	}
+}
+
+impl Default for Interpreter {
+	fn default() -> Self {
+		Self::new()
+	}
+}
@@ -1,14 +1,119 @@
-mod interpreter;
-use std::{fs, env::args};
+use std::{fs, env, io::{self, Read}, path::Path, process::exit, time::Duration};
+use lambis::{repl, Backend, Interpreter, LambisError, Limits};
+
+fn report(error: LambisError, file: &str, source: &str) -> ! {
+	eprint!("{}", error.render(file, source));
+	exit(1);
+}
+
+// `lambis fmt [--check] [files]` rewrites the files in place, or stdin to stdout without any,
+// and with `--check` only lists the ones that would change and fails if there are any
+fn fmt(argv: &mut dyn Iterator<Item=String>) {
+	let mut check: bool = false;
+	let mut files: Vec<String> = vec![];
+	for arg in argv {
+		match arg.as_str() {
+			"--check" => check = true,
+			_ => files.push(arg),
+		}
+	}
+
+	if files.is_empty() {
+		let mut source: String = String::new();
+		if let Err(error) = io::stdin().read_to_string(&mut source) {
+			eprintln!("error: cannot read stdin: {error}");
+			exit(1);
+		}
+
+		let formatted: String = lambis::format(source.as_str()).unwrap_or_else(|error| report(error, "<stdin>", source.as_str()));
+		if !check {
+			print!("{formatted}");
+		} else if formatted != source {
+			eprintln!("<stdin> is not formatted");
+			exit(1);
+		}
+
+		return;
+	}
+
+	let mut unformatted: bool = false;
+	for file in files {
+		let source: String = fs::read_to_string(&file).unwrap_or_else(|error| {
+			eprintln!("error: cannot read «{file}»: {error}");
+			exit(1);
+		});
+
+		let formatted: String = lambis::format(source.as_str()).unwrap_or_else(|error| report(error, file.as_str(), source.as_str()));
+		if formatted == source {
+			continue;
+		}
+
+		if check {
+			eprintln!("{file} is not formatted");
+			unformatted = true;
+		} else if let Err(error) = fs::write(&file, formatted) {
+			eprintln!("error: cannot write «{file}»: {error}");
+			exit(1);
+		}
+	}
+
+	if unformatted {
+		exit(1);
+	}
+}
 
 fn main() {
-	let argv: &mut dyn Iterator<Item=String> = &mut args();
-	let program: String = argv.next().unwrap();
-	let file: String = argv.next().unwrap_or_else(|| panic!("Usage: {program} <file>"));
-
-	interpreter::eval_start(
-		fs::read_to_string(file)
-			.unwrap()
-			.as_str()
-	).unwrap();
+	let argv: &mut dyn Iterator<Item=String> = &mut env::args();
+	let _program: String = argv.next().unwrap();
+
+	let mut backend: Backend = Backend::default();
+	let mut timeout: Option<Duration> = None;
+	let mut file: Option<String> = None;
+	while let Some(arg) = argv.next() {
+		match arg.as_str() {
+			"fmt" if file.is_none() => {
+				fmt(argv);
+				return;
+			}
+			"--vm" => backend = Backend::Vm,
+			"--timeout" => {
+				let seconds: String = argv.next().unwrap_or_default();
+				timeout = Some(seconds
+					.parse::<f64>()
+					.ok()
+					.and_then(|seconds: f64| Duration::try_from_secs_f64(seconds).ok())
+					.unwrap_or_else(|| {
+						eprintln!("error: «--timeout» expects a number of seconds, got «{seconds}»");
+						exit(1);
+					}));
+			}
+			_ if file.is_none() => file = Some(arg),
+			_ => {
+				eprintln!("error: unexpected argument «{arg}»");
+				exit(1);
+			}
+		}
+	}
+
+	let Some(file) = file else {
+		repl::run(backend, timeout);
+		return
+	};
+
+	let source: String = fs::read_to_string(&file).unwrap_or_else(|error| {
+		eprintln!("error: cannot read «{file}»: {error}");
+		exit(1);
+	});
+
+	let mut interpreter: Interpreter = Interpreter::new();
+	interpreter.set_backend(backend);
+	interpreter.set_limits(Limits {
+		timeout,
+		..Limits::default()
+	});
+
+	if let Err(error) = interpreter.eval_file(Path::new(&file), source.as_str()) {
+		let (name, text): (String, &str) = interpreter.source_of(&error).unwrap_or((file, source.as_str()));
+		report(error, name.as_str(), text);
+	}
 }
@@ -0,0 +1,243 @@
+use std::{
+	env,
+	fs,
+	io::{self, Write},
+	path::{Path, PathBuf},
+	time::Duration,
+};
+
+use crate::interpreter::{
+	self,
+	error::{LambisError, Span},
+	parser::{self, lexer},
+};
+
+const HELP: &str = "\
+:env            list every defined variable
+:ast <expr>     show the syntax tree of <expr>
+:tokens <expr>  show the tokens of <expr>
+:load <file>    evaluate <file> in the current environment
+:reset          forget every definition
+:history        show previous inputs
+:help           show this message
+:quit           leave the repl";
+
+struct Repl {
+	backend: interpreter::Backend,
+	// applies to every statement on its own
+	timeout: Option<Duration>,
+	environment: interpreter::Environment,
+	history: Vec<String>,
+	history_file: Option<PathBuf>,
+}
+
+// input is incomplete while brackets are left open, a string is unterminated
+// or the last line ends in the middle of a definition, a lambda or a switch arm
+fn is_complete(source: &str) -> bool {
+	let tokens: Vec<(lexer::Token, Span)> = match lexer::lex(source) {
+		Ok(tokens) => tokens,
+		Err(error) => return error.span().end < source.len(),
+	};
+
+	let pending: bool = match tokens.as_slice() {
+		[.., (lexer::Token::Lambda, _), (params, _), (lexer::Token::Period, _)] => matches!(params, lexer::Token::Ident(_)) || params.is_keyword(),
+		[.., (last, _)] => matches!(last,
+			lexer::Token::Equal
+			| lexer::Token::Arrow
+			| lexer::Token::Lambda
+			| lexer::Token::Dollar
+		),
+		[] => false,
+	};
+
+	let mut depth: isize = 0;
+	for (token, _) in tokens {
+		match token {
+			lexer::Token::OpenParen | lexer::Token::OpenBrace | lexer::Token::OpenBracket => depth += 1,
+			lexer::Token::CloseParen | lexer::Token::CloseBrace | lexer::Token::CloseBracket => depth -= 1,
+			_ => {}
+		}
+	}
+
+	depth <= 0 && !pending
+}
+
+fn show(value: &parser::Value) -> String {
+	if let parser::Value::String(s) = value {
+		format!("{s:?}")
+	} else {
+		value.to_string()
+	}
+}
+
+fn new_environment(backend: interpreter::Backend, timeout: Option<Duration>) -> interpreter::Environment {
+	let mut environment: interpreter::Environment = interpreter::Environment::new(env::current_dir().unwrap_or_else(|_| PathBuf::from(".")));
+	environment.backend = backend;
+	environment.limits.timeout = timeout;
+	// trying things out means defining them again
+	environment.redefine = true;
+	environment
+}
+
+impl Repl {
+	fn new(backend: interpreter::Backend, timeout: Option<Duration>) -> Self {
+		let history_file: Option<PathBuf> = env::var_os("HOME")
+			.map(|home| PathBuf::from(home).join(".lambis_history"));
+
+		let history: Vec<String> = history_file
+			.as_ref()
+			.and_then(|path: &PathBuf| fs::read_to_string(path).ok())
+			.map(|content: String| content.lines().map(str::to_owned).collect())
+			.unwrap_or_default();
+
+		Self {
+			backend,
+			timeout,
+			environment: new_environment(backend, timeout),
+			history,
+			history_file,
+		}
+	}
+
+	fn remember(&mut self, input: &str) {
+		let entry: String = input.trim().replace('\n', " ");
+		if entry.is_empty() {
+			return;
+		}
+
+		if let Some(path) = &self.history_file {
+			if let Ok(mut file) = fs::OpenOptions::new().create(true).append(true).open(path) {
+				let _: io::Result<()> = writeln!(file, "{entry}");
+			}
+		}
+
+		self.history.push(entry);
+	}
+
+	fn run_source(&mut self, source: &str) -> Result<Option<parser::Value>, LambisError> {
+		let nodes: Vec<parser::ASTNode> = parser::parse(source)?;
+		let mut result: Option<parser::Value> = None;
+
+		for node in nodes {
+			let printing: bool = matches!(node, parser::ASTNode::Print(..));
+			let value: parser::Value = self.environment.run(vec![node])?;
+			result = if printing { None } else { Some(value) };
+		}
+
+		Ok(result)
+	}
+
+	fn report(&self, error: LambisError, file: &str, source: &str) {
+		let (name, text): (String, &str) = self.environment
+			.source_of(&error)
+			.unwrap_or((file.to_owned(), source));
+		eprint!("{}", error.render(name.as_str(), text));
+	}
+
+	fn eval(&mut self, source: &str, file: &str) {
+		match self.run_source(source) {
+			Ok(Some(parser::Value::None)) | Ok(None) => {}
+			Ok(Some(value)) => println!("{}", show(&value)),
+			Err(error) => self.report(error, file, source),
+		}
+	}
+
+	// imports of the loaded file are resolved from its own directory, like when it is run
+	fn load(&mut self, file: &str) {
+		let source: String = match fs::read_to_string(file) {
+			Ok(source) => source,
+			Err(error) => return eprintln!("error: cannot read «{file}»: {error}"),
+		};
+
+		match self.environment.eval_file(Path::new(file), source.as_str()) {
+			Ok(parser::Value::None) => {}
+			Ok(value) => println!("{}", show(&value)),
+			Err(error) => self.report(error, file, source.as_str()),
+		}
+	}
+
+	fn command(&mut self, line: &str) -> bool {
+		let (command, rest): (&str, &str) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+		let rest: &str = rest.trim();
+
+		match command {
+			":quit" | ":q" => return false,
+			":help" | ":h" => println!("{HELP}"),
+
+			":env" => {
+				let mut names: Vec<&String> = self.environment.variables.keys().collect();
+				names.sort();
+
+				for name in names {
+					println!("{name} = {}", show(&self.environment.variables[name]));
+				}
+			}
+
+			":ast" => match parser::parse(rest) {
+				Ok(nodes) => for node in nodes {
+					println!("{node:#?}");
+				}
+				Err(error) => eprint!("{}", error.render("<repl>", rest)),
+			}
+
+			":tokens" => match lexer::lex(rest) {
+				Ok(tokens) => for (token, span) in tokens {
+					println!("{}:{}\t{token:?}", span.line, span.column);
+				}
+				Err(error) => eprint!("{}", error.render("<repl>", rest)),
+			}
+
+			":load" => self.load(rest),
+
+			":reset" => self.environment = new_environment(self.backend, self.timeout),
+
+			":history" => for (index, entry) in self.history.iter().enumerate() {
+				println!("{:>4}  {entry}", index + 1);
+			}
+
+			_ => eprintln!("error: unknown command «{command}», try :help"),
+		}
+
+		true
+	}
+}
+
+/// The interactive loop of the `lambis` binary, reading statements from stdin until it closes.
+pub fn run(backend: interpreter::Backend, timeout: Option<Duration>) {
+	let mut repl: Repl = Repl::new(backend, timeout);
+	let stdin: io::Stdin = io::stdin();
+	let mut buffer: String = String::new();
+
+	println!("lambis repl, type :help for help");
+
+	loop {
+		print!("{}", if buffer.is_empty() {"λ> "} else {".. "});
+		let _: io::Result<()> = io::stdout().flush();
+
+		let mut line: String = String::new();
+		match stdin.read_line(&mut line) {
+			Ok(0) | Err(_) => break,
+			Ok(_) => {}
+		}
+
+		if buffer.is_empty() && line.trim_start().starts_with(':') {
+			repl.remember(line.as_str());
+			if !repl.command(line.trim()) {
+				break;
+			}
+
+			continue;
+		}
+
+		buffer += line.as_str();
+		if !is_complete(buffer.as_str()) {
+			continue;
+		}
+
+		let source: String = std::mem::take(&mut buffer);
+		repl.remember(source.as_str());
+		repl.eval(source.as_str(), "<repl>");
+	}
+
+	println!();
+}
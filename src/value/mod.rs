@@ -0,0 +1,80 @@
+use std::fmt;
+
+use crate::interpreter::{bigint::BigInt, parser, rational::Rational};
+
+/// What evaluating lambis gives back, and what the embedding program passes in.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+	None,
+	Word(String),
+	String(String),
+	Bool(bool),
+	Integer(i128),
+	/// Only for integers that don't fit in `Integer`.
+	BigInt(BigInt),
+	/// Exact results of divisions, never with a denominator of 1.
+	Rational(Rational),
+	Decimal(f64),
+	Array(Vec<Value>),
+	Function(Function),
+}
+
+/// A function of a script, a builtin or a native, opaque to the embedding program.
+/// It can be set on another interpreter with the backend that made it.
+#[derive(Clone, PartialEq)]
+pub struct Function(parser::Value);
+
+impl fmt::Debug for Function {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "Function({})", self.0)
+	}
+}
+
+impl fmt::Display for Function {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "{}", self.0)
+	}
+}
+
+impl fmt::Display for Value {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "{}", self.clone().into_runtime())
+	}
+}
+
+impl Value {
+	// lazy arguments nothing has forced yet can't be read without running them, so they stay opaque
+	pub(crate) fn from_runtime(value: parser::Value) -> Self {
+		match value {
+			parser::Value::None => Value::None,
+			parser::Value::Word(word) => Value::Word(word),
+			parser::Value::String(string) => Value::String(string),
+			parser::Value::Bool(bool) => Value::Bool(bool),
+			parser::Value::Integer(int) => Value::Integer(int),
+			parser::Value::BigInt(int) => Value::BigInt(int),
+			parser::Value::Rational(rational) => Value::Rational(rational),
+			parser::Value::Decimal(dec) => Value::Decimal(dec),
+			parser::Value::Array(items) => Value::Array(items.into_iter().map(Value::from_runtime).collect()),
+			parser::Value::Thunk(thunk) => match &*thunk.borrow() {
+				parser::Thunk::Forced(value) => Value::from_runtime(value.clone()),
+				_ => Value::Function(Function(parser::Value::Thunk(thunk.clone()))),
+			},
+			function => Value::Function(Function(function)),
+		}
+	}
+
+	pub(crate) fn into_runtime(self) -> parser::Value {
+		match self {
+			Value::None => parser::Value::None,
+			Value::Word(word) => parser::Value::Word(word),
+			Value::String(string) => parser::Value::String(string),
+			Value::Bool(bool) => parser::Value::Bool(bool),
+			Value::Integer(int) => parser::Value::Integer(int),
+			Value::BigInt(int) => parser::Value::BigInt(int),
+			Value::Rational(rational) => parser::Value::Rational(rational),
+			Value::Decimal(dec) => parser::Value::Decimal(dec),
+			Value::Array(items) => parser::Value::Array(items.into_iter().map(Value::into_runtime).collect()),
+			Value::Function(Function(function)) => function,
+		}
+	}
+}
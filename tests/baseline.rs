@@ -0,0 +1,65 @@
+// the examples as they were before booleans, strings and arrays were added,
+// which have to keep running and printing what they did
+use std::{
+	fs,
+	path::{Path, PathBuf},
+	process::{Command, Output, Stdio},
+};
+
+use lambis::{Backend, Interpreter, Value};
+
+fn baseline(name: &str) -> PathBuf {
+	Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join("baseline").join(name)
+}
+
+#[test]
+fn baseline_examples_print_what_they_did() {
+	let evens: String = (-10..=10).step_by(2).map(|n: i32| format!("{n}\n")).collect();
+	for (name, printed) in [
+		("array.li", evens.as_str()),
+		("comments.li", ""),
+		("conditional.li", ""),
+		("factorial.li", "3628800\n"),
+		("switchCase.li", "5\n10\n69\n"),
+		("test.li", "-8\n"),
+	] {
+		for flags in [&[][..], &["--vm"][..]] {
+			let output: Output = Command::new(env!("CARGO_BIN_EXE_lambis"))
+				.args(flags)
+				.arg(baseline(name))
+				.stdin(Stdio::null())
+				.output()
+				.unwrap();
+
+			assert!(output.status.success(), "{name} {flags:?} failed: {}", String::from_utf8_lossy(&output.stderr));
+			assert_eq!(String::from_utf8_lossy(&output.stdout), printed, "{name} {flags:?}");
+		}
+	}
+}
+
+#[test]
+fn booleans_still_count_as_numbers_in_the_baseline_logic() {
+	let source: String = fs::read_to_string(baseline("conditional.li")).unwrap();
+	for backend in [Backend::TreeWalker, Backend::Vm] {
+		let mut interpreter: Interpreter = Interpreter::new();
+		interpreter.set_backend(backend);
+		interpreter.eval(&source).unwrap();
+
+		for (probe, expected) in [
+			("(not true)", Value::Integer(0)),
+			("(not false)", Value::Integer(1)),
+			("(or false true)", Value::Bool(true)),
+			("(or false false)", Value::Bool(false)),
+			("(and true (not false))", Value::Bool(true)),
+			("(and true false)", Value::Bool(false)),
+			("(xor true false)", Value::Bool(true)),
+			("(xor true true)", Value::Bool(false)),
+			("(ifELse (and 1 true) λx. \"then\" λx. \"else\")", Value::String("then".to_owned())),
+			("(ifELse (or 0 false) λx. \"then\" λx. \"else\")", Value::String("else".to_owned())),
+			("true + true", Value::Integer(2)),
+			("[false] * 3", Value::Integer(0)),
+		] {
+			assert_eq!(interpreter.eval(probe), Ok(expected), "{probe} on {backend:?}");
+		}
+	}
+}
@@ -0,0 +1,63 @@
+use std::{
+	env,
+	fs,
+	io::Write,
+	path::PathBuf,
+	process::{Command, Output, Stdio},
+};
+
+// both backends keep the calls waiting for each other on the heap
+#[test]
+fn deep_recursion_runs_on_both_backends() {
+	let script: PathBuf = env::temp_dir().join(format!("lambis-recursion-{}.li", std::process::id()));
+	fs::write(&script, "sum = λn. n ${\n\t0 -> 0\n\t_ -> n + (sum n-1)\n}.\n! (sum 5000)\n! (sum 100000)\n").unwrap();
+
+	for backend in [None, Some("--vm")] {
+		let output: Output = Command::new(env!("CARGO_BIN_EXE_lambis")).args(backend).arg(&script).output().unwrap();
+		assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+		assert_eq!(String::from_utf8_lossy(&output.stdout), "12502500\n5000050000\n");
+	}
+
+	fs::remove_file(&script).unwrap();
+}
+
+#[test]
+fn input_is_read_from_stdin() {
+	let script: PathBuf = env::temp_dir().join(format!("lambis-input-{}.li", std::process::id()));
+	fs::write(&script, "name = ?.\nn = ??.\n! name ++ \"!\"\n! n * 2\n! ?\n").unwrap();
+
+	for backend in [None, Some("--vm")] {
+		let mut child = Command::new(env!("CARGO_BIN_EXE_lambis"))
+			.args(backend)
+			.arg(&script)
+			.stdin(Stdio::piped())
+			.stdout(Stdio::piped())
+			.spawn()
+			.unwrap();
+
+		child.stdin.take().unwrap().write_all(b"bob\n21\n").unwrap();
+		let output: Output = child.wait_with_output().unwrap();
+		assert!(output.status.success());
+		assert_eq!(String::from_utf8_lossy(&output.stdout), "bob!\n42\nNothing\n");
+	}
+
+	fs::remove_file(&script).unwrap();
+}
+
+#[test]
+fn the_repl_lets_definitions_be_redone() {
+	for backend in [None, Some("--vm")] {
+		let mut child = Command::new(env!("CARGO_BIN_EXE_lambis"))
+			.args(backend)
+			.stdin(Stdio::piped())
+			.stdout(Stdio::piped())
+			.stderr(Stdio::piped())
+			.spawn()
+			.unwrap();
+
+		child.stdin.take().unwrap().write_all(b"x = \"a\".\nx = \"b\".\n! x\n").unwrap();
+		let output: Output = child.wait_with_output().unwrap();
+		assert_eq!(String::from_utf8_lossy(&output.stderr), "");
+		assert!(String::from_utf8_lossy(&output.stdout).contains("λ> b\n"));
+	}
+}
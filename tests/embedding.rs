@@ -0,0 +1,96 @@
+use std::num::NonZeroUsize;
+
+use lambis::{Backend, Interpreter, LambisError, Limits, Value};
+
+fn interpreters() -> [Interpreter; 2] {
+	[Backend::TreeWalker, Backend::Vm].map(|backend: Backend| {
+		let mut interpreter: Interpreter = Interpreter::new();
+		interpreter.set_backend(backend);
+		interpreter.register("sum", NonZeroUsize::new(2).unwrap(), |args: Vec<Value>| match args.as_slice() {
+			[Value::Integer(a), Value::Integer(b)] => Ok(Value::Integer(a + b)),
+			_ => Err("expected two integers".to_owned()),
+		});
+		interpreter
+	})
+}
+
+fn message(result: Result<Value, LambisError>) -> String {
+	result.unwrap_err().message().to_owned()
+}
+
+#[test]
+fn natives_and_builtins_are_applied_partially() {
+	for mut interpreter in interpreters() {
+		assert_eq!(interpreter.eval("add = (sum 1)\n(add 2)").unwrap(), Value::Integer(3));
+		assert_eq!(interpreter.eval("(sum 2 3)").unwrap(), Value::Integer(5));
+		assert_eq!(interpreter.eval("(not false)").unwrap(), Value::Bool(true));
+	}
+}
+
+#[test]
+fn too_many_arguments() {
+	for mut interpreter in interpreters() {
+		assert_eq!(message(interpreter.eval("(sum 1 2 3)")), "too much arguments (3/2) for «sum»");
+		assert_eq!(message(interpreter.eval("added = (sum 1)\n(added 2 3)")), "too much arguments (3/2) for «sum»");
+		assert_eq!(message(interpreter.eval("(not true false)")), "too much arguments (2/1) for «not»");
+	}
+}
+
+#[test]
+fn native_errors_name_the_native() {
+	for mut interpreter in interpreters() {
+		assert_eq!(message(interpreter.eval("(sum 'a 1)")), "sum: expected two integers");
+	}
+}
+
+#[test]
+fn sandbox_refuses_file_builtins() {
+	for mut interpreter in interpreters() {
+		interpreter.set_limits(Limits {
+			sandbox: true,
+			..Limits::default()
+		});
+
+		assert_eq!(message(interpreter.eval("(fileread \"Cargo.toml\")")), "«fileread» is disabled in the sandbox");
+		assert_eq!(message(interpreter.eval("(filewrite \"x\")")), "«filewrite» is disabled in the sandbox");
+	}
+}
+
+#[test]
+fn functions_only_move_between_interpreters_of_the_same_backend() {
+	let [mut walker, mut vm] = interpreters();
+	let lambda: Value = walker.eval("λx.(x + 1)").unwrap();
+	let closure: Value = vm.eval("λx.(x + 1)").unwrap();
+
+	assert!(matches!(lambda, Value::Function(..)));
+	assert!(matches!(closure, Value::Function(..)));
+	assert_eq!(lambda.to_string(), "<λx.>");
+	assert_eq!(closure.to_string(), "<λx.>");
+	assert_eq!(vm.set("inc", Value::Array(vec![lambda.clone()])).unwrap_err().message(), "«inc» holds a function the vm can't call");
+	assert_eq!(walker.set("inc", closure.clone()).unwrap_err().message(), "«inc» holds a function the tree-walker can't call");
+
+	let [mut walker2, mut vm2] = interpreters();
+	walker2.set("inc", lambda).unwrap();
+	vm2.set("inc", closure).unwrap();
+	assert_eq!(walker2.eval("(inc 1)").unwrap(), Value::Integer(2));
+	assert_eq!(vm2.eval("(inc 1)").unwrap(), Value::Integer(2));
+}
+
+#[test]
+fn globals_are_read_and_set_as_values() {
+	for mut interpreter in interpreters() {
+		interpreter.eval("xs = '(1 'a \"b\" true (1 / 3) (2 ^ 200))\ninc = λx.(x + 1)").unwrap();
+		let Some(Value::Array(items)) = interpreter.get("xs") else {
+			panic!("«xs» is not an array");
+		};
+
+		assert_eq!(items[..4], [Value::Integer(1), Value::Word("a".to_owned()), Value::String("b".to_owned()), Value::Bool(true)]);
+		assert_eq!(items[4].to_string(), "1/3");
+		assert!(matches!(items[5], Value::BigInt(..)));
+		assert!(matches!(interpreter.get("inc"), Some(Value::Function(..))));
+		assert_eq!(interpreter.get("missing"), None);
+
+		interpreter.set("ys", Value::Array(items)).unwrap();
+		assert_eq!(interpreter.eval("|ys|").unwrap(), Value::Integer(6));
+	}
+}
@@ -0,0 +1,55 @@
+use std::{
+	fs,
+	path::{Path, PathBuf},
+	process::{Command, Output, Stdio},
+};
+
+fn examples() -> Vec<PathBuf> {
+	let mut examples: Vec<PathBuf> = fs::read_dir(Path::new(env!("CARGO_MANIFEST_DIR")).join("examples"))
+		.unwrap()
+		.map(|entry| entry.unwrap().path())
+		.filter(|path: &PathBuf| path.extension().is_some_and(|extension| extension == "li"))
+		.collect();
+
+	examples.sort();
+	examples
+}
+
+fn run(example: &Path, flags: &[&str]) -> Output {
+	Command::new(env!("CARGO_BIN_EXE_lambis"))
+		.args(flags)
+		.arg(example.file_name().unwrap())
+		.current_dir(example.parent().unwrap())
+		.stdin(Stdio::null())
+		.output()
+		.unwrap()
+}
+
+#[test]
+fn examples_run_the_same_on_both_backends() {
+	let examples: Vec<PathBuf> = examples();
+	assert!(!examples.is_empty());
+
+	for example in examples {
+		let walked: Output = run(&example, &[]);
+		let compiled: Output = run(&example, &["--vm"]);
+		let name: String = example.display().to_string();
+
+		assert!(walked.status.success(), "{name} failed: {}", String::from_utf8_lossy(&walked.stderr));
+		assert!(compiled.status.success(), "{name} failed with --vm: {}", String::from_utf8_lossy(&compiled.stderr));
+		assert_eq!(String::from_utf8_lossy(&walked.stdout), String::from_utf8_lossy(&compiled.stdout), "{name} prints differently with --vm");
+	}
+}
+
+#[test]
+fn examples_are_formatted() {
+	let examples: Vec<PathBuf> = examples();
+	let checked: Output = Command::new(env!("CARGO_BIN_EXE_lambis"))
+		.arg("fmt")
+		.arg("--check")
+		.args(&examples)
+		.output()
+		.unwrap();
+
+	assert!(checked.status.success(), "{}", String::from_utf8_lossy(&checked.stderr));
+}
@@ -0,0 +1,121 @@
+use std::{num::NonZeroUsize, thread, time::Duration};
+
+use lambis::{Backend, Cancellation, Interpreter, LambisError, Limit, Limits, Value};
+
+// the parser recurses for every nested expression, which takes a lot more stack
+// in debug builds than in release ones, so everything runs with room for it to reach its limit
+fn with_stack(test: impl FnOnce() + Send + 'static) {
+	thread::Builder::new()
+		.stack_size(64 << 20)
+		.spawn(test)
+		.unwrap()
+		.join()
+		.unwrap();
+}
+
+fn eval(backend: Backend, limits: Limits, source: &str) -> Result<Value, LambisError> {
+	let mut interpreter: Interpreter = Interpreter::new();
+	interpreter.set_backend(backend);
+	interpreter.set_limits(limits);
+	interpreter.eval(source)
+}
+
+fn sandbox() -> Limits {
+	Limits {
+		sandbox: true,
+		..Limits::default()
+	}
+}
+
+#[test]
+fn deep_input_is_refused_before_it_runs() {
+	with_stack(|| {
+		let chain: String = vec!["1"; 100_000].join(" + ");
+		let products: String = vec!["2"; 100_000].join(" * ");
+		let parens: String = "(".repeat(50_000) + "1" + &")".repeat(50_000);
+		let patterns: String = format!("x = 1 ${{ {}x{} -> x }}", "(".repeat(50_000), " . ())".repeat(50_000));
+
+		for source in [chain, products, parens, patterns] {
+			for backend in [Backend::TreeWalker, Backend::Vm] {
+				let error: LambisError = eval(backend, sandbox(), &source).unwrap_err();
+				assert_eq!(error, LambisError::limit(Limit::Nesting, error.span()).with_hint("the limit is 200"));
+			}
+		}
+	});
+}
+
+#[test]
+fn nesting_is_only_bounded_when_asked() {
+	with_stack(|| {
+		let chain: String = vec!["1"; 300].join(" + ");
+		let parens: String = "(".repeat(300) + "1" + &")".repeat(300);
+		let limits: Limits = Limits {
+			nesting: Some(50),
+			..Limits::default()
+		};
+
+		for backend in [Backend::TreeWalker, Backend::Vm] {
+			assert_eq!(eval(backend, Limits::default(), &chain).unwrap(), Value::Integer(300));
+			assert_eq!(eval(backend, Limits::default(), &parens).unwrap(), Value::Integer(1));
+
+			let error: LambisError = eval(backend, limits, &chain).unwrap_err();
+			assert_eq!(error, LambisError::limit(Limit::Nesting, error.span()).with_hint("the limit is 50"));
+			assert_eq!(eval(backend, limits, &vec!["1"; 40].join(" + ")).unwrap(), Value::Integer(40));
+		}
+	});
+}
+
+#[test]
+fn the_sandbox_bounds_deep_recursion() {
+	with_stack(|| {
+		let source: &str = "sum = λn. n ${\n\t0 -> 0\n\t_ -> n + (sum n-1)\n}.\n(sum 100000)";
+		let shallow: &str = "sum = λn. n ${\n\t0 -> 0\n\t_ -> n + (sum n-1)\n}.\n(sum 10)";
+
+		for backend in [Backend::TreeWalker, Backend::Vm] {
+			let error: LambisError = eval(backend, sandbox(), source).unwrap_err();
+			assert_eq!(error, LambisError::limit(Limit::Depth, error.span()).with_hint("the limit is 1000"));
+			assert_eq!(eval(backend, sandbox(), shallow).unwrap(), Value::Integer(55));
+		}
+	});
+}
+
+#[test]
+fn a_timeout_stops_endless_recursion() {
+	let source: &str = "loop = λn. (loop n+1).\n(loop 0)";
+	let limits: Limits = Limits {
+		timeout: Some(Duration::from_millis(50)),
+		..Limits::default()
+	};
+
+	for backend in [Backend::TreeWalker, Backend::Vm] {
+		let error: LambisError = eval(backend, limits, source).unwrap_err();
+		assert_eq!(error, LambisError::cancelled(error.span()).with_hint("it ran for longer than 50ms"));
+	}
+}
+
+#[test]
+fn a_cancellation_stops_only_the_running_evaluation() {
+	for backend in [Backend::TreeWalker, Backend::Vm] {
+		let mut interpreter: Interpreter = Interpreter::new();
+		interpreter.set_backend(backend);
+		let cancellation: Cancellation = interpreter.cancellation();
+		interpreter.register("stop", NonZeroUsize::new(1).unwrap(), move |_: Vec<Value>| {
+			cancellation.cancel();
+			Ok(Value::None)
+		});
+
+		let error: LambisError = interpreter.eval("loop = λn. (loop n+1).\n(stop 0)\n(loop 0)").unwrap_err();
+		assert_eq!(error, LambisError::cancelled(error.span()));
+		assert_eq!(interpreter.eval("(1 + 1)").unwrap(), Value::Integer(2));
+	}
+}
+
+#[test]
+fn a_cancellation_made_between_evaluations_is_dropped() {
+	for backend in [Backend::TreeWalker, Backend::Vm] {
+		let mut interpreter: Interpreter = Interpreter::new();
+		interpreter.set_backend(backend);
+		interpreter.cancellation().cancel();
+		assert_eq!(interpreter.eval("(1 + 1)").unwrap(), Value::Integer(2));
+	}
+}
@@ -0,0 +1,126 @@
+use lambis::{Backend, Interpreter, LambisError, Value};
+
+// evaluates `source` on both backends, which have to agree
+fn eval(source: &str) -> Result<Value, LambisError> {
+	let mut results: Vec<Result<Value, LambisError>> = [Backend::TreeWalker, Backend::Vm]
+		.into_iter()
+		.map(|backend: Backend| {
+			let mut interpreter: Interpreter = Interpreter::new();
+			interpreter.set_backend(backend);
+			interpreter.eval(source)
+		}).collect();
+
+	let vm: Result<Value, LambisError> = results.pop().unwrap();
+	let walked: Result<Value, LambisError> = results.pop().unwrap();
+	assert_eq!(walked, vm, "the backends disagree on {source}");
+	walked
+}
+
+fn word(word: &str) -> Value {
+	Value::Word(word.to_owned())
+}
+
+fn error(source: &str) -> String {
+	eval(source).unwrap_err().message().to_owned()
+}
+
+#[test]
+fn numeric_literals_match_like_equality() {
+	assert_eq!(eval("1 = 1.0").unwrap(), Value::Bool(true));
+	assert_eq!(eval("x = 1\nx ${\n\t1.0 -> 'one\n\t_ -> 'other\n}").unwrap(), word("one"));
+	assert_eq!(eval("x = 2.0\nx ${\n\t2 -> 'two\n\t_ -> 'other\n}").unwrap(), word("two"));
+	assert_eq!(eval("x = 1/2\nx ${\n\t0.5 -> 'half\n\t_ -> 'other\n}").unwrap(), word("half"));
+	assert_eq!(eval("x = '(1 2)\nx ${\n\t'(1.0 2.0) -> 'same\n\t_ -> 'other\n}").unwrap(), word("same"));
+	assert_eq!(eval("x = 3\nx ${\n\t3.5 -> 'no\n\t_ -> 'other\n}").unwrap(), word("other"));
+}
+
+#[test]
+fn guards() {
+	let classify: &str = "classify = λn. n ${\n\tx when x > 10 -> 'big\n\tx when x < 0 -> 'negative\n\t_ -> 'small\n}.\n";
+	assert_eq!(eval(format!("{classify}(classify 20)").as_str()).unwrap(), word("big"));
+	assert_eq!(eval(format!("{classify}(classify 0-5)").as_str()).unwrap(), word("negative"));
+	assert_eq!(eval(format!("{classify}(classify 5)").as_str()).unwrap(), word("small"));
+	assert_eq!(error("x = 1\nx ${\n\t_ when 1 -> 'one\n}"), "when: expected <Bool>, got Integer(1)");
+}
+
+#[test]
+fn ranges() {
+	let digit: &str = "digit = λn. n ${\n\t0..9 -> 'digit\n\t_ -> 'other\n}.\n";
+	assert_eq!(eval(format!("{digit}(digit 0)").as_str()).unwrap(), word("digit"));
+	assert_eq!(eval(format!("{digit}(digit 9)").as_str()).unwrap(), word("digit"));
+	assert_eq!(eval(format!("{digit}(digit 4.5)").as_str()).unwrap(), word("digit"));
+	assert_eq!(eval(format!("{digit}(digit 10)").as_str()).unwrap(), word("other"));
+	assert_eq!(eval(format!("{digit}(digit 'nine)").as_str()).unwrap(), word("other"));
+}
+
+#[test]
+fn cons_and_array_patterns() {
+	let describe: &str = "describe = λl. l ${\n\t() -> 'empty\n\t'(a) -> a\n\t'(a b) -> a + b\n\t(1 . (h . _)) -> h\n\t(_ . rest) -> rest\n\t_ -> 'other\n}.\n";
+	assert_eq!(eval(format!("{describe}(describe '())").as_str()).unwrap(), word("empty"));
+	assert_eq!(eval(format!("{describe}(describe '(7))").as_str()).unwrap(), Value::Integer(7));
+	assert_eq!(eval(format!("{describe}(describe '(2 3))").as_str()).unwrap(), Value::Integer(5));
+	assert_eq!(eval(format!("{describe}(describe '(1 8 9))").as_str()).unwrap(), Value::Integer(8));
+	assert_eq!(eval(format!("{describe}(describe '(2 8 9))").as_str()).unwrap(), Value::Array(vec![Value::Integer(8), Value::Integer(9)]));
+	assert_eq!(eval(format!("{describe}(describe 'word)").as_str()).unwrap(), word("other"));
+}
+
+#[test]
+fn unreachable_cases_are_parse_errors() {
+	assert_eq!(error("x = 1\nx ${\n\t_ -> 1\n\t2 -> 3\n}"), "unreachable case «2»");
+	assert_eq!(error("x = 1\nx ${\n\tn -> 1\n\t'(a) -> 3\n}"), "unreachable case «'(a)»");
+	assert_eq!(error("x = 1\nx ${\n\t(h . t) -> 1\n\t(1 . '()) -> 3\n}"), "unreachable case «(1 . '())»");
+	assert_eq!(error("x = 1\nx ${\n\t1..5 -> 1\n\t1..5 -> 3\n}"), "unreachable case «1..5»");
+	assert_eq!(eval("x = 2\nx ${\n\tn when n > 5 -> 1\n\t2 -> 3\n}").unwrap(), Value::Integer(3));
+	assert_eq!(eval("x = 2\nx ${\n\t'(a b) -> 1\n\t'(a) -> 3\n\t_ -> 4\n}").unwrap(), Value::Integer(4));
+}
+
+#[test]
+fn names_bind_and_pins_compare() {
+	let same: &str = "y = 3\nsame = λx. x ${\n\t=y -> 'same\n\t_ -> 'other\n}.\n";
+	assert_eq!(eval(format!("{same}(same 3)").as_str()).unwrap(), word("same"));
+	assert_eq!(eval(format!("{same}(same 4)").as_str()).unwrap(), word("other"));
+
+	// a bare name binds even when a global has it
+	assert_eq!(eval("y = 3\nx = 4\nx ${\n\ty -> y\n}").unwrap(), Value::Integer(4));
+	assert_eq!(eval("y = 3\nx = 4\nx ${\n\t=y -> 'same\n\ty -> y\n}").unwrap(), Value::Integer(4));
+}
+
+#[test]
+fn pins_are_expressions_of_the_switch_scope() {
+	let next: &str = "inc = λx. (x + 1).\nnext = λ(n x). x ${\n\t=n-1 -> 'before\n\t=(inc n) -> 'after\n\t'(=n rest) -> rest\n\t_ -> 'other\n}.\n";
+	assert_eq!(eval(format!("{next}(next 5 4)").as_str()).unwrap(), word("before"));
+	assert_eq!(eval(format!("{next}(next 5 6)").as_str()).unwrap(), word("after"));
+	assert_eq!(eval(format!("{next}(next 5 '(5 9))").as_str()).unwrap(), Value::Integer(9));
+	assert_eq!(eval(format!("{next}(next 5 '(4 9))").as_str()).unwrap(), word("other"));
+	assert_eq!(eval("f = λn. n ${\n\t=n -> 'itself\n}.\n(f 2)").unwrap(), word("itself"));
+	assert_eq!(error("x = 1\nx ${\n\t=missing -> 1\n}"), "variable «missing» is undefined in current context");
+}
+
+#[test]
+fn negative_literals() {
+	let sign: &str = "sign = λn. n ${\n\t-1 -> 'minus\n\t-5..-2 -> 'low\n\t-1.5 -> 'half\n\t0 -> 'zero\n\t-1..1 -> 'near\n\t_ -> 'other\n}.\n";
+	assert_eq!(eval(format!("{sign}(sign 0-1)").as_str()).unwrap(), word("minus"));
+	assert_eq!(eval(format!("{sign}(sign 0-3)").as_str()).unwrap(), word("low"));
+	assert_eq!(eval(format!("{sign}(sign 0-3/2)").as_str()).unwrap(), word("half"));
+	assert_eq!(eval(format!("{sign}(sign 0)").as_str()).unwrap(), word("zero"));
+	assert_eq!(eval(format!("{sign}(sign 1/2)").as_str()).unwrap(), word("near"));
+	assert_eq!(eval(format!("{sign}(sign 0-9)").as_str()).unwrap(), word("other"));
+	assert_eq!(error("x = 1\nx ${\n\t-1 -> 1\n\t-1 -> 2\n}"), "unreachable case «-1»");
+}
+
+#[test]
+fn cases_starting_a_line_with_an_operator_are_not_operations() {
+	assert_eq!(eval("y = 2\nx = 2\nx ${\n\t1 -> 1\n\t=y -> 2\n}").unwrap(), Value::Integer(2));
+	assert_eq!(eval("x = 0 - 1\nx ${\n\t1 -> x\n\t-1 -> x * 2\n}").unwrap(), Value::Integer(-2));
+	assert_eq!(eval("x = 3\nx ${\n\t_ -> x\n\t\t+ 1\n}").unwrap(), Value::Integer(4));
+}
+
+#[test]
+fn calls_in_guards_need_parentheses() {
+	let even: &str = "even = λn. (n % 2 = 0).\nx = 4\n";
+	assert_eq!(eval(format!("{even}x ${{\n\tn when (even n) -> 'even\n\t_ -> 'odd\n}}").as_str()).unwrap(), word("even"));
+
+	let error: LambisError = eval(format!("{even}x ${{\n\tn when even n -> 'even\n}}").as_str()).unwrap_err();
+	assert_eq!(error.message(), "expected «->», got «n»");
+	assert_eq!(error.hint(), Some("a call in a guard needs parentheses, as in `when (f x)`"));
+}